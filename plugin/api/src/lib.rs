@@ -0,0 +1,135 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+//! The interface between sush and the builtins loaded by `enable -f`.
+//!
+//! A plugin is a shared library that exports the three symbols below.
+//! Only C types cross the boundary, so a plugin and sush may be built
+//! with different versions of the compiler. The `plugin!` macro writes
+//! the symbols from a list of Rust functions.
+//!
+//! - `sush_plugin_abi_version() -> u32`: returns `ABI_VERSION`
+//! - `sush_plugin_has(name) -> c_int`: returns 1 if the plugin has the builtin `name`
+//! - `sush_plugin_call(host, argc, argv) -> c_int`: runs the builtin `argv[0]`,
+//!   or returns `PANIC_STATUS` if it panics
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+
+pub const ABI_VERSION: u32 = 1;
+
+/* the exit status of a builtin that panicked */
+pub const PANIC_STATUS: c_int = 1;
+
+pub const ABI_VERSION_SYMBOL: &str = "sush_plugin_abi_version";
+pub const HAS_SYMBOL: &str = "sush_plugin_has";
+pub const CALL_SYMBOL: &str = "sush_plugin_call";
+
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+pub type HasFn = unsafe extern "C" fn(name: *const c_char) -> c_int;
+pub type CallFn = unsafe extern "C" fn(host: *const Host, argc: c_int, argv: *const *const c_char) -> c_int;
+
+/* The functions given by sush. The strings returned by get_var
+ * must be released with free_string. */
+#[repr(C)]
+pub struct Host {
+    pub abi_version: u32,
+    pub core: *mut c_void,
+    pub get_var: unsafe extern "C" fn(core: *mut c_void, name: *const c_char) -> *mut c_char,
+    pub set_var: unsafe extern "C" fn(core: *mut c_void, name: *const c_char, value: *const c_char) -> c_int,
+    pub free_string: unsafe extern "C" fn(s: *mut c_char),
+    pub write: unsafe extern "C" fn(fd: c_int, buf: *const u8, len: usize) -> isize,
+}
+
+/* a safe wrapper of Host for the builtins written in Rust */
+pub struct Shell<'a> {
+    host: &'a Host,
+}
+
+impl Shell<'_> {
+    /// # Safety
+    /// `host` must be the pointer given to `sush_plugin_call`, and the
+    /// returned value must not be used after the call returns.
+    pub unsafe fn new<'a>(host: *const Host) -> Shell<'a> {
+        Shell { host: &*host }
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<String> {
+        let name = CString::new(name).ok()?;
+        unsafe {
+            let s = (self.host.get_var)(self.host.core, name.as_ptr());
+            if s.is_null() {
+                return None;
+            }
+            let ans = CStr::from_ptr(s).to_string_lossy().to_string();
+            (self.host.free_string)(s);
+            Some(ans)
+        }
+    }
+
+    pub fn set_var(&self, name: &str, value: &str) -> bool {
+        let (name, value) = match (CString::new(name), CString::new(value)) {
+            (Ok(n), Ok(v)) => (n, v),
+            _ => return false,
+        };
+        unsafe { (self.host.set_var)(self.host.core, name.as_ptr(), value.as_ptr()) == 0 }
+    }
+
+    pub fn print(&self, s: &str) {
+        unsafe { (self.host.write)(1, s.as_ptr(), s.len()); }
+    }
+
+    pub fn eprint(&self, s: &str) {
+        unsafe { (self.host.write)(2, s.as_ptr(), s.len()); }
+    }
+}
+
+/// # Safety
+/// `s` must be a valid C string.
+pub unsafe fn to_string(s: *const c_char) -> String {
+    CStr::from_ptr(s).to_string_lossy().to_string()
+}
+
+/// # Safety
+/// `argv` must hold `argc` valid C strings.
+pub unsafe fn to_args(argc: c_int, argv: *const *const c_char) -> Vec<String> {
+    (0..argc as usize).map(|i| to_string(*argv.add(i))).collect()
+}
+
+/// Exports the builtins of a plugin.
+///
+/// ```ignore
+/// fn hello(shell: &Shell, args: &[String]) -> i32 { ... }
+/// sush_plugin_api::plugin!("hello" => hello);
+/// ```
+#[macro_export]
+macro_rules! plugin {
+    ($($name:literal => $func:path),+ $(,)?) => {
+        #[no_mangle]
+        pub extern "C" fn sush_plugin_abi_version() -> u32 {
+            $crate::ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn sush_plugin_has(name: *const std::ffi::c_char) -> std::ffi::c_int {
+            match $crate::to_string(name).as_str() {
+                $($name)|+ => 1,
+                _ => 0,
+            }
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn sush_plugin_call(host: *const $crate::Host, argc: std::ffi::c_int,
+                                                  argv: *const *const std::ffi::c_char) -> std::ffi::c_int {
+            let shell = $crate::Shell::new(host);
+            let args = $crate::to_args(argc, argv);
+            /* a panic must not unwind into sush */
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                match args.first().map(|a| a.as_str()) {
+                    $(Some($name) => $func(&shell, &args),)+
+                    _ => 127,
+                }
+            }));
+            result.unwrap_or($crate::PANIC_STATUS)
+        }
+    };
+}
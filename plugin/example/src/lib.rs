@@ -0,0 +1,35 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+//! Load with `enable -f libsush_plugin_example.so hello upper`.
+
+use sush_plugin_api::Shell;
+
+fn hello(shell: &Shell, args: &[String]) -> i32 {
+    let name = args.get(1).map(|a| a.as_str()).unwrap_or("world");
+    shell.print(&format!("Hello, {}!\n", name));
+    0
+}
+
+/* upper NAME ...: converts the values of the variables to uppercase */
+fn upper(shell: &Shell, args: &[String]) -> i32 {
+    if args.len() < 2 {
+        shell.eprint("upper: usage: upper name [name ...]\n");
+        return 2;
+    }
+
+    let mut exit_status = 0;
+    for name in &args[1..] {
+        let value = shell.get_var(name).unwrap_or_default();
+        if ! shell.set_var(name, &value.to_uppercase()) {
+            shell.eprint(&format!("upper: {}: cannot set the variable\n", name));
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
+
+sush_plugin_api::plugin!(
+    "hello" => hello,
+    "upper" => upper,
+);
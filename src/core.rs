@@ -3,26 +3,30 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 pub mod builtins;
+pub mod call_stack;
 pub mod completion;
 pub mod database;
+pub mod hash_table;
 pub mod history;
 pub mod jobtable;
 pub mod options;
+pub mod xtrace;
 
 use crate::{error, proc_ctrl, signal};
 use crate::error::exec::ExecError;
 use self::database::DataBase;
 use self::options::Options;
 use self::completion::CompletionInfo;
-use std::collections::HashMap;
+use self::hash_table::HashEntry;
+use self::builtins::loadable::Loadable;
+use std::collections::{BTreeMap, HashMap};
 use std::os::fd::{FromRawFd, OwnedFd};
 use std::{io, env, path};
-use nix::{fcntl, unistd};
+use nix::{fcntl, libc, unistd};
 use nix::sys::signal::Signal;
 use nix::sys::time::{TimeSpec, TimeVal};
 use nix::unistd::Pid;
 use crate::core::jobtable::JobEntry;
-use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
 pub struct MeasuredTime {
@@ -49,13 +53,23 @@ pub struct ShellCore {
     rewritten_history: HashMap<usize, String>,
     pub history: Vec<String>,
     pub builtins: HashMap<String, fn(&mut ShellCore, &mut Vec<String>) -> i32>,
-    pub sigint: Arc<AtomicBool>,
-    pub trapped: Vec<(Arc<AtomicBool>, String)>,
+    pub disabled_builtins: HashMap<String, fn(&mut ShellCore, &mut Vec<String>) -> i32>,
+    pub loadables: HashMap<String, Loadable>,
+    pub sigint: AtomicBool,
+    pub signal_pipe: Option<OwnedFd>, // the read end of the self-pipe
+    pub traps: BTreeMap<i32, String>, // 0: EXIT
+    pub parent_traps: BTreeMap<i32, String>, // listed by trap in a subshell until it sets one
+    pub trap_defaults: HashMap<i32, libc::sigaction>,
+    pub running_trap: bool,
+    pub judged_inside: bool, // the exit status is of a command in a compound command
+    pub command_substituted: bool,
     pub read_stdin: bool,
     pub is_subshell: bool,
     pub source_function_level: i32,
+    pub function_level: i32,
     pub source_level: i32,
     pub eval_level: i32,
+    pub comsub_level: i32,
     pub loop_level: i32,
     pub break_counter: i32,
     pub continue_counter: i32,
@@ -64,31 +78,37 @@ pub struct ShellCore {
     pub job_table: Vec<JobEntry>,
     pub job_table_priority: Vec<usize>,
     current_dir: Option<path::PathBuf>, // the_current_working_directory
+    pub dir_stack: Vec<String>, // DIRSTACK without the current directory
     pub completion_info: HashMap<String, CompletionInfo>,
     pub completion_functions: HashMap<String, String>,
     pub default_completion_functions: String,
                                                                             //options for compgen
     pub measured_time: MeasuredTime,
     pub options: Options,
-    pub shopts: Options,
     pub suspend_e_option: bool,
     pub script_name: String,
-    pub exit_script: String,
+    pub start_time: i64,
+    pub hash_table: HashMap<String, HashEntry>,
+    pub getopts_state: (usize, usize, Vec<String>), // (OPTIND, position in the argument, arguments)
+    pub arg_frames: Vec<Vec<String>>, // arguments of the function calls and sourced files, innermost first
+    hash_path: String,
 }
 
 impl ShellCore {
     pub fn new() -> ShellCore {
         let mut core = ShellCore{
             db: DataBase::new(),
-            sigint: Arc::new(AtomicBool::new(false)),
+            sigint: AtomicBool::new(false),
             read_stdin: true,
-            options: Options::new_as_basic_opts(),
-            shopts: Options::new_as_shopts(),
+            options: Options::new(),
             script_name: "-".to_string(),
+            start_time: nix::time::clock_gettime(nix::time::ClockId::CLOCK_REALTIME)
+                        .map(|t| t.tv_sec()).unwrap_or(0),
             ..Default::default()
         };
 
         core.init_current_directory();
+        core.set_dirstack_param();
         core.set_initial_parameters();
         core.set_builtins();
         signal::ignore(Signal::SIGPIPE);
@@ -171,8 +191,8 @@ impl ShellCore {
         proc_ctrl::set_pgid(self, pid, pgid);
         let _ = self.set_subshell_parameters();
         self.job_table.clear();
-
-        self.exit_script.clear();
+        signal::init_subshell(self);
+        signal::reset_traps_in_subshell(self);
     }
 
     pub fn init_current_directory(&mut self) {
@@ -196,17 +216,21 @@ impl ShellCore {
     pub fn set_current_directory(&mut self, path: &path::PathBuf) -> Result<(), io::Error> {
         env::set_current_dir(path)?;
         self.current_dir = Some(path.clone());
+        self.set_dirstack_param();
         Ok(())
     }
 
-    pub fn get_ps4(&mut self) -> String {
-        let ps4 = self.db.get_param("PS4").unwrap_or_default().trim_end().to_string();
-        let mut multi_ps4 = ps4.to_string();
-        for _ in 0..(self.source_level + self.eval_level) {
-            multi_ps4 += &ps4;
-        }
+    pub fn get_dir_stack(&mut self) -> Vec<String> {
+        let current = match self.get_current_directory() {
+            Some(path) => path.display().to_string(),
+            None       => String::new(),
+        };
+        [vec![current], self.dir_stack.clone()].concat()
+    }
 
-        multi_ps4
+    pub fn set_dirstack_param(&mut self) {
+        let stack = self.get_dir_stack();
+        let _ = self.db.set_array("DIRSTACK", stack, Some(0));
     }
 
     pub fn replace_alias(&mut self, word: &mut String) -> bool {
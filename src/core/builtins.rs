@@ -3,25 +3,38 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 mod alias;
+mod caller;
 mod cd;
 pub mod completion;
+mod dir_stack;
+mod enable;
 mod getopts;
+mod hash;
+pub mod help;
 mod history;
 mod job_commands;
+pub mod loadable;
+mod mapfile;
 pub mod parameter;
 pub mod option;
 mod printf;
 mod pwd;
 mod read;
 mod source;
+mod times;
 mod trap;
+mod type_;
+mod ulimit;
+mod umask;
 mod loop_control;
 mod unset;
 
 use crate::{error, proc_ctrl, Feeder, Script, ShellCore};
 use crate::elements::command::simple::SimpleCommand;
+use crate::elements::expr::arithmetic::ArithmeticExpr;
+use crate::error::exec::ExecError;
 use crate::elements::io::pipe::Pipe;
-use crate::utils::{arg, exit, file};
+use crate::utils::{arg, exit};
 
 impl ShellCore {
     pub fn set_builtins(&mut self) {
@@ -30,6 +43,7 @@ impl ShellCore {
         self.builtins.insert("bg".to_string(), job_commands::bg);
         self.builtins.insert("break".to_string(), loop_control::break_);
         self.builtins.insert("builtin".to_string(), builtin);
+        self.builtins.insert("caller".to_string(), caller::caller);
         self.builtins.insert("cd".to_string(), cd::cd);
         self.builtins.insert("command".to_string(), command);
         self.builtins.insert("compgen".to_string(), completion::compgen);
@@ -37,27 +51,43 @@ impl ShellCore {
         self.builtins.insert("compopt".to_string(), completion::compopt);
         self.builtins.insert("continue".to_string(), loop_control::continue_);
         self.builtins.insert("declare".to_string(), parameter::declare);
+        self.builtins.insert("dirs".to_string(), dir_stack::dirs);
+        self.builtins.insert("disown".to_string(), job_commands::disown);
+        self.builtins.insert("enable".to_string(), enable::enable);
         self.builtins.insert("eval".to_string(), eval);
         self.builtins.insert("exit".to_string(), exit);
         self.builtins.insert("false".to_string(), false_);
         self.builtins.insert("fg".to_string(), job_commands::fg);
         self.builtins.insert("getopts".to_string(), getopts::getopts);
+        self.builtins.insert("hash".to_string(), hash::hash);
+        self.builtins.insert("help".to_string(), help::help);
         self.builtins.insert("history".to_string(), history::history);
         self.builtins.insert("jobs".to_string(), job_commands::jobs);
+        self.builtins.insert("kill".to_string(), job_commands::kill);
+        self.builtins.insert("let".to_string(), let_);
         self.builtins.insert("local".to_string(), parameter::local);
+        self.builtins.insert("mapfile".to_string(), mapfile::mapfile);
+        self.builtins.insert("popd".to_string(), dir_stack::popd);
         self.builtins.insert("printf".to_string(), printf::printf);
+        self.builtins.insert("pushd".to_string(), dir_stack::pushd);
         self.builtins.insert("pwd".to_string(), pwd::pwd);
         self.builtins.insert("read".to_string(), read::read);
+        self.builtins.insert("readarray".to_string(), mapfile::mapfile);
         self.builtins.insert("return".to_string(), loop_control::return_);
         self.builtins.insert("set".to_string(), option::set);
         self.builtins.insert("trap".to_string(), trap::trap);
         self.builtins.insert("shift".to_string(), option::shift);
         self.builtins.insert("shopt".to_string(), option::shopt);
+        self.builtins.insert("suspend".to_string(), job_commands::suspend);
+        self.builtins.insert("ulimit".to_string(), ulimit::ulimit);
+        self.builtins.insert("umask".to_string(), umask::umask);
         self.builtins.insert("unalias".to_string(), alias::unalias);
         self.builtins.insert("unset".to_string(), unset::unset);
         self.builtins.insert("source".to_string(), source::source);
         self.builtins.insert(".".to_string(), source::source);
+        self.builtins.insert("times".to_string(), times::times);
         self.builtins.insert("true".to_string(), true_);
+        self.builtins.insert("type".to_string(), type_::type_);
         self.builtins.insert("wait".to_string(), job_commands::wait);
     }
 }
@@ -84,27 +114,23 @@ pub fn command_v(words: &mut Vec<String>, core: &mut ShellCore, large_v: bool) -
     let mut return_value = 1;
 
     for com in words.iter() {
-        if core.aliases.contains_key(com) {
-            match large_v {
-                true  => println!("{} is aliased to `{}'", &com, core.aliases[com]),
-                false => println!("alias {}='{}'", &com, &core.aliases[com]),
-            }
-        }else if core.builtins.contains_key(com) {
-            return_value = 0;
-
-            match large_v {
-                true  => println!("{} is a shell builtin", &com),
-                false => println!("{}", &com),
-            }
-        }else if let Some(path) = file::search_command(&com) {
-            return_value = 0;
-            match large_v {
-                true  => println!("{} is {}", &com, &path),
-                false => println!("{}", &com),
-            }
-        }else if large_v {
-            let msg = format!("command: {}: not found", com);
-            error::print(&msg, core);
+        let kind = match type_::kinds(core, com, false, true).pop() {
+            Some(k) => k,
+            None => {
+                if large_v {
+                    let msg = format!("command: {}: not found", com);
+                    error::print(&msg, core);
+                }
+                continue;
+            },
+        };
+
+        return_value = 0;
+        match (large_v, kind) {
+            (true, k) => println!("{}", k.describe(com)),
+            (false, type_::Kind::Alias(s)) => println!("alias {}='{}'", &com, &s),
+            (false, type_::Kind::File(path, _)) => println!("{}", &path),
+            (false, _) => println!("{}", &com),
         }
     }
 
@@ -130,6 +156,11 @@ pub fn command(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         return command_v(&mut words, core, false);
     }
 
+    run_command(core, words)
+}
+
+/* runs a builtin or an external command without function lookup */
+fn run_command(core: &mut ShellCore, mut words: Vec<String>) -> i32 {
     if core.builtins.contains_key(&words[0]) {
         return core.builtins[&words[0]](core, &mut words);
     }
@@ -146,19 +177,60 @@ pub fn command(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 
 pub fn eval(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     let args = arg::consume_after_options(args, 1);
-    let mut feeder = Feeder::new(&args.join(" "));
+    let mut feeder = Feeder::new("");
+    feeder.set_string(&args.join(" "));
+    if let Ok(n) = core.db.get_param("LINENO").unwrap_or_default().parse::<usize>() {
+        feeder.lineno = n;
+    }
 
     core.eval_level += 1;
-    match Script::parse(&mut feeder, core, false){
-        Ok(Some(mut s)) => {let _ = s.exec(core); },
-        Err(e) => e.print(core),
-        _        => {},
+    while feeder.feed_line(core).is_ok() {
+        match Script::parse(&mut feeder, core, false){
+            Ok(Some(mut s)) => {let _ = s.exec(core); },
+            Err(e) => {
+                e.print(core);
+                break;
+            },
+            _        => {},
+        }
     }
 
     core.eval_level -= 1;
     core.db.exit_status
 }
 
+pub fn let_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() < 2 {
+        error::print("let: expression expected", core);
+        return 1;
+    }
+
+    let mut ans = String::new();
+    for expr in &args[1..] {
+        let mut feeder = Feeder::new(expr);
+        let result = match ArithmeticExpr::parse(&mut feeder, core, false) {
+            Ok(Some(mut a)) if feeder.len() == 0 => a.eval(core),
+            Ok(_) => Err(ExecError::SyntaxError(format!("in expression (error token is \"{}\")",
+                                                        feeder.consume(feeder.len())))),
+            Err(e) => Err(ExecError::ParseError(e)),
+        };
+
+        match result {
+            Ok(s) => ans = s,
+            Err(e) => {
+                let msg: String = From::from(&e);
+                error::print(&format!("let: {}", msg), core);
+                return 1;
+            },
+        }
+    }
+
+    match ans.as_str() {
+        "0" => 1,
+        _   => 0,
+    }
+}
+
 pub fn exit(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     eprintln!("exit");
     if args.len() > 1 {
@@ -21,7 +21,8 @@ pub fn alias(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 
 pub fn unalias(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if args.len() <= 1 {
-        println!("unalias: usage: unalias [-a] name [name ...]");
+        super::help::print_usage("unalias");
+        return 2;
     }
 
     if args.contains(&"-a".to_string()) {
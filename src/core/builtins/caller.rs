@@ -0,0 +1,43 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+
+fn usage(core: &mut ShellCore, msg: &str) -> i32 {
+    error::print(&format!("caller: {}", msg), core);
+    super::help::print_usage("caller");
+    2
+}
+
+pub fn caller(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let lines = core.db.get_array_all("BASH_LINENO");
+    let funcs = core.db.get_array_all("FUNCNAME");
+    let sources = core.db.get_array_all("BASH_SOURCE");
+    let source = |n: usize| sources.get(n).cloned().unwrap_or("NULL".to_string());
+
+    if args.len() < 2 {
+        return match lines.first() {
+            Some(line) => {
+                println!("{} {}", line, source(1));
+                0
+            },
+            None => 1,
+        };
+    }
+
+    if args[1].starts_with('-') && args[1].len() > 1 {
+        return usage(core, &format!("{}: invalid option", &args[1]));
+    }
+    let n = match args[1].parse::<usize>() {
+        Ok(n) => n,
+        Err(_) => return usage(core, &format!("{}: invalid number", &args[1])),
+    };
+
+    match (lines.get(n), funcs.get(n+1)) {
+        (Some(line), Some(func)) => {
+            println!("{} {} {}", line, func, source(n+1));
+            0
+        },
+        _ => 1,
+    }
+}
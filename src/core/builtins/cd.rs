@@ -3,62 +3,197 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{error, ShellCore};
-use crate::utils::file;
+use crate::utils::{arg, file, file_check};
+use nix::errno::Errno;
+use std::{fs, io};
 
 pub fn cd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() > 2 {
-        eprintln!("sush: cd: too many arguments");
+    let (opts, dirs) = match arg::getopt(&args[1..], "LPe@") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("cd: {}", msg), core);
+            super::help::print_usage("cd");
+            return 2;
+        },
+    };
+
+    let mut physical = core.db.flags.contains('P');
+    for opt in opts {
+        match opt.0 {
+            'L' => physical = false,
+            'P' => physical = true,
+            _ => {},
+        }
+    }
+
+    if dirs.len() > 1 {
+        error::print("cd: too many arguments", core);
         return 1;
     }
 
-    if args.len() == 1 { //only "cd"
-        return cd_1arg(core, args);
+    let (dir, print) = match dirs.first().map(|d| d.as_str()) {
+        None => match core.db.has_value("HOME") {
+            true  => (core.db.get_param("HOME").unwrap_or_default(), false),
+            false => {
+                error::print("cd: HOME not set", core);
+                return 1;
+            },
+        },
+        Some("-") => match core.db.get_param("OLDPWD") {
+            Ok(old) if ! old.is_empty() => (old, true),
+            _ => {
+                error::print("cd: OLDPWD not set", core);
+                return 1;
+            },
+        },
+        Some(d) => (d.to_string(), false),
+    };
+
+    if dir.is_empty() {
+        return 0;
     }
 
-    if args[1] == "-" { // cd -
-        cd_oldpwd(core, args)
-    }else{ // cd /some/dir
-        set_oldpwd(core);
-        change_directory(core, args)
+    let (target, print) = match find_directory(core, &dir) {
+        Some(target) => (target, true),
+        None         => (dir.clone(), print),
+    };
+
+    if let Err(msg) = change_directory(core, &target, physical) {
+        error::print(&format!("cd: {}: {}", &dir, msg), core);
+        return 1;
     }
-}
 
-fn cd_1arg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    let var = "~".to_string();
-    args.push(var);
-    set_oldpwd(core);
-    change_directory(core, args)
+    if print {
+        println!("{}", core.db.get_param("PWD").unwrap_or_default());
+    }
+    0
 }
 
-fn cd_oldpwd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    match core.db.get_param("OLDPWD") {
-        Ok(old) => {
-            println!("{}", &old);
-            args[1] = old.to_string();
-        },
-        Err(_) => {
-            error::print("cd: OLDPWD not set", core);
-            return 1;
-        },
+/* looks for the directory with CDPATH, cdable_vars and cdspell,
+ * and returns the path only when it should be printed after cd */
+fn find_directory(core: &mut ShellCore, dir: &str) -> Option<String> {
+    let relative = ! dir.starts_with('/') && dir != "." && dir != ".."
+                   && ! dir.starts_with("./") && ! dir.starts_with("../");
+
+    let cdpath = core.db.get_param("CDPATH").unwrap_or_default();
+    if relative && ! cdpath.is_empty() {
+        for base in cdpath.split(':') {
+            let candidate = match base.is_empty() {
+                true  => dir.to_string(),
+                false => format!("{}/{}", base.trim_end_matches('/'), dir),
+            };
+            if file::make_canonical_path(core, &candidate).is_dir() {
+                return match base.is_empty() || base == "." {
+                    true  => None,
+                    false => Some(candidate),
+                };
+            }
+        }
+    }
+
+    if file::make_canonical_path(core, dir).is_dir() {
+        return None;
+    }
+
+    if core.options.query("cdable_vars") && crate::utils::is_param(dir) {
+        if let Ok(value) = core.db.get_param(dir) {
+            if ! value.is_empty() {
+                return Some(value);
+            }
+        }
     }
 
-    set_oldpwd(core);
-    change_directory(core, args)
+    if core.db.flags.contains('i') && core.options.query("cdspell") {
+        return spell_correct(dir);
+    }
+    None
+}
+
+fn errno_desc(e: io::Error) -> String {
+    match e.raw_os_error() {
+        Some(n) => Errno::from_raw(n).desc().to_string(),
+        None    => e.to_string(),
+    }
 }
 
-fn set_oldpwd(core: &mut ShellCore) {
-    if let Some(old) = core.get_current_directory() {
+pub fn change_directory(core: &mut ShellCore, dir: &str, physical: bool) -> Result<(), String> {
+    let path = match physical {
+        true  => file::make_absolute_path(core, dir).canonicalize().map_err(errno_desc)?,
+        false => file::make_canonical_path(core, dir),
+    };
+
+    let old = core.get_current_directory();
+    core.set_current_directory(&path).map_err(errno_desc)?;
+
+    if let Some(old) = old {
         let _ = core.db.set_param("OLDPWD", &old.display().to_string(), Some(0));
+    }
+    let _ = core.db.set_param("PWD", &path.display().to_string(), Some(0));
+    Ok(())
+}
+
+/* 0: same, 1: transposition, 2: one char is changed, added or missing, 3: other */
+fn spell_distance(entry: &[u8], guess: &[u8]) -> usize {
+    let i = match entry.iter().zip(guess.iter()).position(|(a, b)| a != b) {
+        Some(i) => i,
+        None if entry.len() == guess.len() => return 0,
+        None => entry.len().min(guess.len()),
     };
+    let (s, t) = (&entry[i..], &guess[i..]);
+
+    if ! t.is_empty() {
+        if ! s.is_empty() {
+            if s.len() > 1 && t.len() > 1 && s[0] == t[1] && s[1] == t[0] && s[2..] == t[2..] {
+                return 1;
+            }
+            if s[1..] == t[1..] {
+                return 2;
+            }
+        }
+        if s == &t[1..] {
+            return 2;
+        }
+    }
+    if ! s.is_empty() && &s[1..] == t {
+        return 2;
+    }
+    3
+}
+
+fn spell_correct_name(dir: &str, name: &str) -> Option<String> {
+    let mut best: Option<(usize, String)> = None;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let entry = file::oss_to_name(&entry.file_name());
+        let dist = spell_distance(entry.as_bytes(), name.as_bytes());
+        if dist < best.as_ref().map(|b| b.0).unwrap_or(3) {
+            best = Some((dist, entry));
+        }
+    }
+    best.map(|b| b.1)
 }
 
-fn change_directory(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    let path = file::make_canonical_path(core, &args[1]);
-    if core.set_current_directory(&path).is_ok() {
-        let _ = core.db.set_param("PWD", &path.display().to_string(), Some(0));
-        0
-    }else{
-        eprintln!("sush: cd: {:?}: No such file or directory", &path);
-        1
+/* corrects each component of the path for cdspell */
+fn spell_correct(dir: &str) -> Option<String> {
+    let mut ans = match dir.starts_with('/') {
+        true  => "/".to_string(),
+        false => String::new(),
+    };
+
+    for name in dir.split('/').filter(|n| ! n.is_empty()) {
+        let base = if ans.is_empty() { "." } else { &ans };
+        let name = match file_check::exists(&format!("{}/{}", base, name)) {
+            true  => name.to_string(),
+            false => spell_correct_name(base, name)?,
+        };
+
+        if ! ans.is_empty() && ! ans.ends_with('/') {
+            ans.push('/');
+        }
+        ans += &name;
+    }
+
+    match file_check::is_dir(&ans) {
+        true  => Some(ans),
+        false => None,
     }
 }
@@ -39,7 +39,7 @@ pub fn compgen_f(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
         return files.iter().map(|f| dir.clone() + &f).collect();
     }
 
-    let mut ans = directory::glob(&dir, &(key.clone() + "*"), core.shopts.query("extglob"));
+    let mut ans = directory::glob(&dir, &(key.clone() + "*"), core.options.query("extglob"));
     if key == "." {
         ans.append(&mut directory::glob(&dir, ".", false));
         ans.append(&mut directory::glob(&dir, "..", false));
@@ -81,6 +81,7 @@ fn replace_args_compgen(args: &mut Vec<String>) -> bool {
         "user" => "-u",
         "setopt" => "-o",
         "stopped" => "-A stopped",
+        "helptopic" => "-A helptopic",
         "job" => "-j",
         a => a,
     };
@@ -132,6 +133,7 @@ pub fn compgen(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         "-u" => compgen_u(core, &mut args),
         "-v" => compgen_v(core, &mut args),
         "-A stopped" => compgen_stopped(core, &mut args),
+        "-A helptopic" => compgen_helptopic(core, &mut args),
         "-W" => {
             if args.len() < 2 {
                 eprintln!("sush: compgen: -W: option requires an argument");
@@ -274,7 +276,7 @@ pub fn compgen_v(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
 pub fn compgen_o(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     let mut commands = vec![];
 
-    let mut options: Vec<String> = core.options.get_keys();
+    let mut options: Vec<String> = core.options.list(false).into_iter().map(|o| o.name).collect();
     commands.append(&mut options);
 
     let head = get_head(args, 2);
@@ -336,6 +338,12 @@ pub fn compgen_stopped(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<Stri
     ans
 }
 
+pub fn compgen_helptopic(_: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
+    let mut ans = super::help::TOPICS.iter().map(|t| t.name.to_string()).collect();
+    drop_unmatch(args, 2, &mut ans);
+    ans
+}
+
 pub fn compgen_j(core: &mut ShellCore, args: &mut Vec<String>) -> Vec<String> {
     let mut ans = vec![];
 
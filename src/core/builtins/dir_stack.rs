@@ -0,0 +1,238 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use super::cd;
+
+fn is_index(arg: &str) -> bool {
+    (arg.starts_with('+') || arg.starts_with('-'))
+    && arg.len() > 1 && arg[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/* +N counts from the left of the list shown by dirs, -N from the right */
+fn to_index(arg: &str, len: usize) -> Option<usize> {
+    let n = arg[1..].parse::<usize>().ok()?;
+    if n >= len {
+        return None;
+    }
+
+    match arg.starts_with('+') {
+        true  => Some(n),
+        false => Some(len - 1 - n),
+    }
+}
+
+fn abbreviate(core: &mut ShellCore, dir: &str) -> String {
+    let home = core.db.get_param("HOME").unwrap_or_default();
+    if home.is_empty() || home == "/" {
+        return dir.to_string();
+    }
+
+    match dir.strip_prefix(&home) {
+        Some(s) if s.is_empty() || s.starts_with('/') => "~".to_string() + s,
+        _ => dir.to_string(),
+    }
+}
+
+fn print_stack(core: &mut ShellCore, long: bool, per_line: bool, verbose: bool) {
+    let stack: Vec<String> = core.get_dir_stack().iter()
+                                 .map(|d| match long {
+                                     true  => d.clone(),
+                                     false => abbreviate(core, d),
+                                 }).collect();
+
+    if verbose {
+        stack.iter().enumerate().for_each(|(i, d)| println!("{:2}  {}", i, d));
+    }else if per_line {
+        stack.iter().for_each(|d| println!("{}", d));
+    }else{
+        println!("{}", stack.join(" "));
+    }
+}
+
+fn usage(core: &mut ShellCore, com: &str, arg: &str) -> i32 {
+    error::print(&format!("{}: {}: invalid number", com, arg), core);
+    super::help::print_usage(com);
+    2
+}
+
+fn out_of_range(core: &mut ShellCore, com: &str, arg: &str) -> i32 {
+    error::print(&format!("{}: {}: directory stack index out of range", com, arg), core);
+    1
+}
+
+fn change_directory(core: &mut ShellCore, com: &str, dir: &str) -> bool {
+    let physical = core.db.flags.contains('P');
+    if let Err(msg) = cd::change_directory(core, dir, physical) {
+        error::print(&format!("{}: {}: {}", com, dir, msg), core);
+        return false;
+    }
+    true
+}
+
+pub fn dirs(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut clear, mut long, mut per_line, mut verbose) = (false, false, false, false);
+    let mut index = None;
+
+    for arg in &args[1..] {
+        if is_index(arg) {
+            index = Some(arg.clone());
+            continue;
+        }
+        if ! arg.starts_with('-') || arg.len() < 2 {
+            return usage(core, "dirs", arg);
+        }
+        for c in arg[1..].chars() {
+            match c {
+                'c' => clear = true,
+                'l' => long = true,
+                'p' => per_line = true,
+                'v' => verbose = true,
+                _ => return usage(core, "dirs", arg),
+            }
+        }
+    }
+
+    if clear {
+        core.dir_stack.clear();
+        core.set_dirstack_param();
+        return 0;
+    }
+
+    if let Some(arg) = index {
+        let stack = core.get_dir_stack();
+        let dir = match to_index(&arg, stack.len()) {
+            Some(i) => stack[i].clone(),
+            None    => return out_of_range(core, "dirs", &arg[1..]),
+        };
+        match long {
+            true  => println!("{}", &dir),
+            false => println!("{}", abbreviate(core, &dir)),
+        }
+        return 0;
+    }
+
+    print_stack(core, long, per_line, verbose);
+    0
+}
+
+fn pushd_rotate(core: &mut ShellCore, arg: &str, no_cd: bool) -> i32 {
+    let mut stack = core.get_dir_stack();
+    let i = match to_index(arg, stack.len()) {
+        Some(i) => i,
+        None    => return out_of_range(core, "pushd", arg),
+    };
+
+    if no_cd {
+        if i > 0 {
+            stack[1..].rotate_left(i - 1);
+        }
+    }else{
+        stack.rotate_left(i);
+        if ! change_directory(core, "pushd", &stack[0].clone()) {
+            return 1;
+        }
+    }
+
+    core.dir_stack = stack[1..].to_vec();
+    0
+}
+
+fn pushd_swap(core: &mut ShellCore) -> i32 {
+    let old = core.get_dir_stack()[0].clone();
+    let dir = core.dir_stack[0].clone();
+    if ! change_directory(core, "pushd", &dir) {
+        return 1;
+    }
+    core.dir_stack[0] = old;
+    0
+}
+
+fn pushd_dir(core: &mut ShellCore, dir: &str, no_cd: bool) -> i32 {
+    if no_cd {
+        core.dir_stack.insert(0, dir.to_string());
+        return 0;
+    }
+
+    let old = core.get_dir_stack()[0].clone();
+    if ! change_directory(core, "pushd", dir) {
+        return 1;
+    }
+    core.dir_stack.insert(0, old);
+    0
+}
+
+pub fn pushd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut no_cd = false;
+    let mut targets = vec![];
+    for arg in &args[1..] {
+        if arg == "-n" {
+            no_cd = true;
+        }else if arg.starts_with('-') && ! is_index(arg) {
+            return usage(core, "pushd", arg);
+        }else{
+            targets.push(arg.clone());
+        }
+    }
+
+    let exit_status = match targets.len() {
+        0 if no_cd => return 0,
+        0 if core.dir_stack.is_empty() => {
+            error::print("pushd: no other directory", core);
+            return 1;
+        },
+        0 => pushd_swap(core),
+        1 if is_index(&targets[0]) => pushd_rotate(core, &targets[0], no_cd),
+        1 => pushd_dir(core, &targets[0], no_cd),
+        _ => {
+            error::print("pushd: too many arguments", core);
+            return 1;
+        },
+    };
+
+    core.set_dirstack_param();
+    if exit_status == 0 {
+        print_stack(core, false, false, false);
+    }
+    exit_status
+}
+
+pub fn popd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut no_cd = false;
+    let mut index = 0;
+    for arg in &args[1..] {
+        if arg == "-n" {
+            no_cd = true;
+        }else if ! is_index(arg) {
+            return usage(core, "popd", arg);
+        }else if core.dir_stack.is_empty() {
+            break;
+        }else{
+            index = match to_index(arg, core.dir_stack.len() + 1) {
+                Some(i) => i,
+                None    => return out_of_range(core, "popd", arg),
+            };
+        }
+    }
+
+    if core.dir_stack.is_empty() {
+        error::print("popd: directory stack empty", core);
+        return 1;
+    }
+
+    if index > 0 {
+        core.dir_stack.remove(index - 1);
+    }else if no_cd {
+        core.dir_stack.remove(0);
+    }else{
+        let dir = core.dir_stack[0].clone();
+        if ! change_directory(core, "popd", &dir) {
+            return 1;
+        }
+        core.dir_stack.remove(0);
+    }
+
+    core.set_dirstack_param();
+    print_stack(core, false, false, false);
+    0
+}
@@ -0,0 +1,88 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use crate::utils::arg;
+use super::loadable;
+
+const SPECIAL_BUILTINS: [&str; 15] = [".", ":", "break", "continue", "eval", "exec", "exit",
+                                      "export", "readonly", "return", "set", "shift",
+                                      "times", "trap", "unset"];
+
+fn print_list(core: &mut ShellCore, enabled: bool, disabled: bool, special: bool) {
+    let mut list: Vec<(String, bool)> = vec![];
+    if enabled {
+        list.extend(core.builtins.keys().map(|k| (k.clone(), true)));
+    }
+    if disabled {
+        list.extend(core.disabled_builtins.keys().map(|k| (k.clone(), false)));
+    }
+    if special {
+        list.retain(|b| SPECIAL_BUILTINS.contains(&b.0.as_str()));
+    }
+    list.sort();
+
+    for (name, on) in list {
+        match on {
+            true  => println!("enable {}", &name),
+            false => println!("enable -n {}", &name),
+        }
+    }
+}
+
+fn switch(core: &mut ShellCore, name: &str, disable: bool) -> bool {
+    let (from, to) = match disable {
+        true  => (&mut core.builtins, &mut core.disabled_builtins),
+        false => (&mut core.disabled_builtins, &mut core.builtins),
+    };
+
+    if let Some(f) = from.remove(name) {
+        to.insert(name.to_string(), f);
+        return true;
+    }
+    to.contains_key(name)
+}
+
+pub fn enable(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, names) = match arg::getopt(&args[1..], "adf:nps") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("enable: {}", msg), core);
+            super::help::print_usage("enable");
+            return 2;
+        },
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    if let Some((_, Some(file))) = opts.iter().find(|o| o.0 == 'f') {
+        let errors = loadable::load(core, file, &names);
+        errors.iter().for_each(|e| error::print(&format!("enable: {}", e), core));
+        return match errors.is_empty() {
+            true  => 0,
+            false => 1,
+        };
+    }
+
+    if names.is_empty() {
+        match (has('a'), has('n')) {
+            (true, _)  => print_list(core, true, true, has('s')),
+            (_, true)  => print_list(core, false, true, has('s')),
+            _          => print_list(core, true, false, has('s')),
+        }
+        return 0;
+    }
+
+    let mut exit_status = 0;
+    for name in &names {
+        if has('d') {
+            if ! loadable::unload(core, name) {
+                error::print(&format!("enable: {}: not dynamically loaded", name), core);
+                exit_status = 1;
+            }
+        }else if ! switch(core, name, has('n')) {
+            error::print(&format!("enable: {}: not a shell builtin", name), core);
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
@@ -1,122 +1,139 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
-use crate::error;
+use crate::{error, utils, ShellCore};
 
-#[derive(Debug)]
-enum Opt {
-    Single(String),
-    WithArg(String),
+enum Found {
+    Opt(char, Option<String>),
+    Illegal(char),
+    NoArg(char),
+    End,
 }
 
-impl Opt {
-    fn is_single(&self, opt: &str) -> bool {
-        match self {
-            Self::Single(s) => return s == opt,
-            _ => false,
-        }
-    }
+/* returns the index of the argument and the position in it */
+fn load_state(core: &mut ShellCore, args: &[String]) -> (usize, usize) {
+    let optind = core.db.get_param("OPTIND").unwrap_or_default()
+                 .parse::<usize>().unwrap_or(1).max(1);
 
-    fn is_witharg(&self, opt: &str) -> bool {
-        match self {
-            Self::WithArg(s) => return s == opt,
-            _ => false,
-        }
+    match &core.getopts_state {
+        (n, pos, a) if *n == optind && a == args => (optind, *pos),
+        _ => (optind, 0), // OPTIND or the arguments have been changed
     }
 }
 
-fn parse(optstring: &str) -> Vec<Opt> {
-    let mut ans = vec![];
-    
-    for c in optstring.chars() {
-        if c == ':' {
-            match ans.pop() {
-                Some(Opt::Single(opt)) => ans.push( Opt::WithArg(opt) ),
-                _ => return vec![],
-            }
-        }
-
-        let opt = format!("-{}", c);
-        ans.push( Opt::Single(opt) );
-    }
-
-    ans
+fn save_state(core: &mut ShellCore, optind: usize, pos: usize, args: &[String]) {
+    core.getopts_state = (optind, pos, args.to_vec());
+    let _ = core.db.set_param("OPTIND", &optind.to_string(), None);
 }
 
-pub fn getopts(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() < 3 {
-        error::print("getopts: usage: getopts optstring name [arg ...]", core);
-        return 2;
+fn next(optstring: &str, args: &[String], optind: &mut usize, pos: &mut usize) -> Found {
+    if *pos == 0 {
+        let arg = match args.get(*optind) {
+            Some(a) if a.starts_with('-') && a != "-" => a,
+            _ => return Found::End,
+        };
+        if arg == "--" {
+            *optind += 1;
+            return Found::End;
+        }
+        *pos = 1;
     }
 
-    let targets = parse(&args[1]);
-    if targets.is_empty() {
-        return 1;
+    let arg = &args[*optind];
+    let c = arg[*pos..].chars().next().unwrap();
+    *pos += c.len_utf8();
+    let rest = arg[*pos..].to_string();
+    if rest.is_empty() {
+        *optind += 1;
+        *pos = 0;
     }
 
-    let name = args[2].clone();
-    let mut index = 1;
+    let with_arg = match optstring.find(c) {
+        Some(n) if c != ':' => optstring[n+c.len_utf8()..].starts_with(':'),
+        _ => return Found::Illegal(c),
+    };
 
-    if let Ok(s) = core.db.get_param("OPTIND") {
-        if let Ok(n) = s.parse::<usize>() {
-            index = n;
-        }
+    if ! with_arg {
+        return Found::Opt(c, None);
     }
 
-    let args = match args.len() > 3 {
-        true  => &args[2..],
-        false => &core.db.position_parameters.last().unwrap(),
-    };
-
-    if args.len() <= index {
-        return 1;
+    if ! rest.is_empty() {
+        *optind += 1;
+        *pos = 0;
+        return Found::Opt(c, Some(rest));
     }
 
-    let arg = args[index].clone();
+    match args.get(*optind) {
+        Some(a) => {
+            *optind += 1;
+            Found::Opt(c, Some(a.clone()))
+        },
+        None => Found::NoArg(c),
+    }
+}
 
-    if ! arg.starts_with("-") {
-        let _ = core.db.set_param(&name, "?", None);
-        let _ = core.db.set_param("OPTARG", "?", None);
-        return 1;
+fn set(core: &mut ShellCore, name: &str, value: &str, optarg: Option<&str>) -> i32 {
+    match optarg {
+        Some(a) => { let _ = core.db.set_param("OPTARG", a, None); },
+        None    => core.db.unset_var("OPTARG"),
     }
 
-    if arg.starts_with("--") {
-        let _ = core.db.set_param(&name, "?", None);
-        let _ = core.db.set_param("OPTARG", "?", None);
-        let _ = core.db.set_param("OPTIND", &(index+1).to_string(), None);
+    if let Err(e) = core.db.set_param(name, value, None) {
+        let msg: String = From::from(&e);
+        error::print(&format!("getopts: {}", msg), core);
         return 1;
     }
+    0
+}
 
-    if targets.iter().any(|t| t.is_single(&arg) ) {
-        let result = core.db.set_param(&name, &arg[1..], None);
-        let _ = core.db.set_param("OPTIND", &(index+1).to_string(), None);
-        let _ = core.db.set_param("OPTARG", "", None);
+pub fn getopts(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() < 3 {
+        super::help::print_usage("getopts");
+        return 2;
+    }
 
-        if let Err(e) = result {
-            let msg = format!("getopts: {:?}", &e);
-            error::print(&msg, core);
-            return 1;
-        }
-        return 0;
+    let name = args[2].clone();
+    if ! utils::is_param(&name) {
+        error::print(&format!("getopts: `{}': not a valid identifier", &name), core);
+        return 1;
     }
 
-    let optarg = match args.len() > index+1 {
-        true  => args[index+1].clone(),
-        false => return 1,
+    let (silent, optstring) = match args[1].strip_prefix(':') {
+        Some(s) => (true, s.to_string()),
+        None    => (false, args[1].clone()),
     };
+    let report = ! silent && core.db.get_param("OPTERR").unwrap_or_default() != "0";
 
-    if targets.iter().any(|t| t.is_witharg(&arg) ) {
-        let result = core.db.set_param(&name, &arg[1..], None);
-        let _ = core.db.set_param("OPTARG", &optarg, None);
-        let _ = core.db.set_param("OPTIND", &(index+2).to_string(), None);
+    /* the list begins with $0 or the name so that OPTIND points at it */
+    let targets = match args.len() > 3 {
+        true  => args[2..].to_vec(),
+        false => core.db.position_parameters.last().unwrap().clone(),
+    };
 
-        if let Err(e) = result {
-            let msg = format!("getopts: {:?}", &e);
-            error::print(&msg, core);
-            return 1;
-        }
+    let (mut optind, mut pos) = load_state(core, &targets);
+    let found = next(&optstring, &targets, &mut optind, &mut pos);
+    save_state(core, optind, pos, &targets);
+
+    let shell = core.db.get_param("0").unwrap_or_default();
+    match found {
+        Found::Opt(c, arg) => set(core, &name, &c.to_string(), arg.as_deref()),
+        Found::End => {
+            set(core, &name, "?", None);
+            1
+        },
+        Found::Illegal(c) if silent => set(core, &name, "?", Some(&c.to_string())),
+        Found::NoArg(c) if silent => set(core, &name, ":", Some(&c.to_string())),
+        Found::Illegal(c) => {
+            if report {
+                eprintln!("{}: illegal option -- {}", &shell, c);
+            }
+            set(core, &name, "?", None)
+        },
+        Found::NoArg(c) => {
+            if report {
+                eprintln!("{}: option requires an argument -- {}", &shell, c);
+            }
+            set(core, &name, "?", None)
+        },
     }
-
-    0
 }
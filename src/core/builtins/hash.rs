@@ -0,0 +1,79 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use crate::utils::arg;
+
+fn print_table(core: &mut ShellCore, reusable: bool) -> i32 {
+    let entries = core.hash_entries();
+    if entries.is_empty() {
+        println!("hash: hash table empty");
+        return 0;
+    }
+
+    if ! reusable {
+        println!("hits\tcommand");
+    }
+    for (name, entry) in entries {
+        match reusable {
+            true  => println!("builtin hash -p {} {}", &entry.path, &name),
+            false => println!("{:4}\t{}", entry.hits, &entry.path),
+        }
+    }
+    0
+}
+
+fn not_found(core: &mut ShellCore, name: &str) -> i32 {
+    error::print(&format!("hash: {}: not found", name), core);
+    1
+}
+
+pub fn hash(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, names) = match arg::getopt(&args[1..], "dlp:rt") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("hash: {}", msg), core);
+            super::help::print_usage("hash");
+            return 2;
+        },
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    if ! core.db.flags.contains('h') {
+        error::print("hash: hashing disabled", core);
+        return 1;
+    }
+
+    if has('r') {
+        core.hash_clear();
+    }
+    if names.is_empty() {
+        return match has('r') && ! has('l') {
+            true  => 0,
+            false => print_table(core, has('l')),
+        };
+    }
+
+    let mut exit_status = 0;
+    for name in &names {
+        if let Some((_, Some(path))) = opts.iter().find(|o| o.0 == 'p') {
+            core.hash_set(name, path);
+        }else if has('d') {
+            if ! core.hash_remove(name) {
+                exit_status = not_found(core, name);
+            }
+        }else if has('t') {
+            match core.hash_get(name) {
+                Some(path) if names.len() > 1 => println!("{}\t{}", name, &path),
+                Some(path) => println!("{}", &path),
+                None => exit_status = not_found(core, name),
+            }
+        }else if name.contains('/') || core.builtins.contains_key(name)
+              || core.db.functions.contains_key(name) {
+            continue;
+        }else if core.hash_search(name).is_none() {
+            exit_status = not_found(core, name);
+        }
+    }
+    exit_status
+}
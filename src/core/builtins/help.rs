@@ -0,0 +1,499 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use crate::utils::{arg, glob};
+
+pub struct Topic {
+    pub name: &'static str,
+    pub synopsis: &'static str,
+    pub short: &'static str,
+    pub long: &'static [&'static str],
+}
+
+const fn topic(name: &'static str, synopsis: &'static str,
+               short: &'static str, long: &'static [&'static str]) -> Topic {
+    Topic { name, synopsis, short, long }
+}
+
+/* the text shown by help and by the usage messages of the builtins */
+pub const TOPICS: &[Topic] = &[
+    topic(".", ". filename [arguments]",
+          "Execute commands from a file in the current shell.", &[
+          "Reads and executes commands from FILENAME. The positional parameters",
+          "are set to ARGUMENTS while FILENAME is read.",
+          "Returns the status of the last command executed in FILENAME.",
+    ]),
+    topic(":", ":",
+          "Null command.", &[
+          "Has no effect. Always succeeds.",
+    ]),
+    topic("alias", "alias [-p] [name[=value] ... ]",
+          "Define or display aliases.", &[
+          "Without arguments, prints the list of aliases.",
+          "With NAME=VALUE, defines an alias NAME for VALUE.",
+          "Returns failure when a NAME has no alias.",
+    ]),
+    topic("bg", "bg [job_spec ...]",
+          "Move jobs to the background.", &[
+          "Resumes each JOB_SPEC in the background as if it had been started",
+          "with `&'. The current job is used without JOB_SPEC.",
+    ]),
+    topic("break", "break [n]",
+          "Exit for, while, or until loops.", &[
+          "Exits from N enclosing loops. N defaults to 1.",
+    ]),
+    topic("builtin", "builtin [shell-builtin [arg ...]]",
+          "Execute shell builtins.", &[
+          "Runs SHELL-BUILTIN with ARGs without function lookup.",
+          "Returns failure when SHELL-BUILTIN is not a shell builtin.",
+    ]),
+    topic("caller", "caller [expr]",
+          "Return the context of the current subroutine call.", &[
+          "Without EXPR, prints \"$line $filename\". With EXPR, prints",
+          "\"$line $subroutine $filename\" of the EXPR-th frame of the call stack.",
+    ]),
+    topic("cd", "cd [-L|[-P [-e]] [-@]] [dir]",
+          "Change the shell working directory.", &[
+          "Changes the current directory to DIR, or to $HOME without DIR.",
+          "DIR \"-\" means $OLDPWD. Relative DIRs are searched in CDPATH.",
+          "",
+          "Options:",
+          "  -L\tfollow symbolic links (default)",
+          "  -P\tuse the physical directory structure",
+    ]),
+    topic("command", "command [-pVv] command [arg ...]",
+          "Execute a simple command or display information about commands.", &[
+          "Runs COMMAND with ARGs without function lookup.",
+          "",
+          "Options:",
+          "  -v\tprint the command word or the path of COMMAND",
+          "  -V\tprint a verbose description of COMMAND",
+    ]),
+    topic("compgen", "compgen [-abcdefgjksuv] [-o option] [-A action] [-G globpat] [-W wordlist] [-F function] [-C command] [-X filterpat] [-P prefix] [-S suffix] [word]",
+          "Display possible completions depending on the options.", &[
+          "Prints the candidates that match WORD.",
+    ]),
+    topic("complete", "complete [-abcdefgjksuv] [-pr] [-DEI] [-o option] [-A action] [-G globpat] [-W wordlist] [-F function] [-C command] [-X filterpat] [-P prefix] [-S suffix] [name ...]",
+          "Specify how arguments are to be completed.", &[
+          "Registers the completion rule of each NAME.",
+          "Without options, prints the registered rules.",
+    ]),
+    topic("compopt", "compopt [-o|+o option] [-DEI] [name ...]",
+          "Modify or display completion options.", &[
+          "Changes the completion options of each NAME, or of the",
+          "completion currently running without NAME.",
+    ]),
+    topic("continue", "continue [n]",
+          "Resume for, while, or until loops.", &[
+          "Resumes the next iteration of the N-th enclosing loop. N defaults to 1.",
+    ]),
+    topic("declare", "declare [-aAfFgiIlnrtux] [name[=value] ...] or declare -p [-aAfFilnrtux] [name ...]",
+          "Set variable values and attributes.", &[
+          "Declares variables and gives them attributes. Without NAMEs,",
+          "prints the variables.",
+          "",
+          "Options:",
+          "  -a\tindexed arrays",
+          "  -A\tassociative arrays",
+          "  -f\tfunctions",
+          "  -i\tintegers",
+          "  -p\tprint the attributes and the value of each NAME",
+          "  -r\treadonly variables",
+          "  -x\texported variables",
+    ]),
+    topic("dirs", "dirs [-clpv] [+N] [-N]",
+          "Display directory stack.", &[
+          "Prints the directory stack made by pushd and popd.",
+          "",
+          "Options:",
+          "  -c\tclear the directory stack",
+          "  -l\tdo not abbreviate the home directory with `~'",
+          "  -p\tprint one entry per line",
+          "  -v\tprint one entry per line with its position",
+          "  +N\tprint the N-th entry from the left",
+          "  -N\tprint the N-th entry from the right",
+    ]),
+    topic("disown", "disown [-h] [-ar] [jobspec ... | pid ...]",
+          "Remove jobs from current shell.", &[
+          "Removes each JOBSPEC from the job table. The current job is used",
+          "without JOBSPEC.",
+          "",
+          "Options:",
+          "  -a\tremove all jobs",
+          "  -h\tkeep the jobs but do not send SIGHUP to them",
+          "  -r\tremove only running jobs",
+    ]),
+    topic("enable", "enable [-a] [-dnps] [-f filename] [name ...]",
+          "Enable and disable shell builtins.", &[
+          "Enables each NAME, or disables it with -n. A disabled builtin",
+          "is looked up in PATH like other commands.",
+          "Without NAMEs, prints the builtins.",
+          "",
+          "Options:",
+          "  -a\tprint all the builtins with their states",
+          "  -d\tremove each NAME loaded with -f",
+          "  -f\tload each NAME from the shared object FILENAME, which is",
+          "    \tsearched in BASH_LOADABLES_PATH if it has no slash",
+          "  -n\tdisable each NAME, or print the disabled builtins",
+          "  -p\tprint in a reusable format",
+          "  -s\tprint only the POSIX special builtins",
+    ]),
+    topic("eval", "eval [arg ...]",
+          "Execute arguments as a shell command.", &[
+          "Joins ARGs into a single string and runs it in the current shell.",
+    ]),
+    topic("exit", "exit [n]",
+          "Exit the shell.", &[
+          "Exits the shell with the status N, or with the last status without N.",
+    ]),
+    topic("false", "false",
+          "Return an unsuccessful result.", &[
+          "Always fails.",
+    ]),
+    topic("fg", "fg [job_spec]",
+          "Move job to the foreground.", &[
+          "Makes JOB_SPEC the current job and resumes it in the foreground.",
+    ]),
+    topic("getopts", "getopts optstring name [arg ...]",
+          "Parse option arguments.", &[
+          "Sets the next option in the positional parameters, or in ARGs,",
+          "to NAME. OPTIND is the index of the next argument and OPTARG is",
+          "set to the argument of an option marked with `:' in OPTSTRING.",
+    ]),
+    topic("hash", "hash [-lr] [-p pathname] [-dt] [name ...]",
+          "Remember or display program locations.", &[
+          "Remembers the full path of each NAME. Without arguments, prints",
+          "the remembered commands.",
+          "",
+          "Options:",
+          "  -d\tforget each NAME",
+          "  -l\tprint in a reusable format",
+          "  -p\tuse PATHNAME as the full path of NAME",
+          "  -r\tforget all the locations",
+          "  -t\tprint the location of each NAME",
+    ]),
+    topic("help", "help [-dms] [pattern ...]",
+          "Display information about builtin commands.", &[
+          "Prints the help of the commands matching PATTERN. Without",
+          "PATTERN, prints the list of the commands.",
+          "",
+          "Options:",
+          "  -d\tprint the short description of each topic",
+          "  -m\tprint in a pseudo-manpage format",
+          "  -s\tprint only the usage synopsis of each topic",
+    ]),
+    topic("history", "history [-c] [-d offset] [n] or history -anrw [filename] or history -ps arg [arg...]",
+          "Display or manipulate the history list.", &[
+          "Prints the history list with line numbers. With N, prints",
+          "only the last N entries.",
+          "",
+          "Options:",
+          "  -c\tclear the history list",
+          "  -d\tdelete the entry at OFFSET",
+    ]),
+    topic("jobs", "jobs [-lnprs] [jobspec ...] or jobs -x command [args]",
+          "Display status of jobs.", &[
+          "Prints the active jobs, or the job of each JOBSPEC.",
+          "",
+          "Options:",
+          "  -l\tprint process IDs too",
+          "  -n\tprint only jobs changed since the last notification",
+          "  -p\tprint only the process IDs",
+          "  -r\tprint only running jobs",
+          "  -s\tprint only stopped jobs",
+    ]),
+    topic("kill", "kill [-s sigspec | -n signum | -sigspec] pid | jobspec ... or kill -l [sigspec]",
+          "Send a signal to a job.", &[
+          "Sends SIGTERM, or the signal named by SIGSPEC or SIGNUM, to each",
+          "process or job. `kill -l' prints the signal names.",
+    ]),
+    topic("let", "let arg [arg ...]",
+          "Evaluate arithmetic expressions.", &[
+          "Evaluates each ARG as an arithmetic expression. Returns failure",
+          "when the last ARG evaluates to 0.",
+    ]),
+    topic("local", "local [option] name[=value] ...",
+          "Define local variables.", &[
+          "Creates variables visible only in the function and its children.",
+          "OPTION is one of the options of declare.",
+    ]),
+    topic("mapfile", "mapfile [-d delim] [-n count] [-O origin] [-s count] [-t] [-u fd] [-C callback] [-c quantum] [array]",
+          "Read lines from the standard input into an indexed array variable.", &[
+          "Reads lines into ARRAY, or into MAPFILE without ARRAY.",
+          "",
+          "Options:",
+          "  -d\tuse DELIM instead of newline",
+          "  -n\tread at most COUNT lines",
+          "  -O\tbegin assigning at the index ORIGIN",
+          "  -s\tdiscard the first COUNT lines",
+          "  -t\tremove the trailing DELIM of each line",
+          "  -u\tread from the file descriptor FD",
+    ]),
+    topic("popd", "popd [-n] [+N | -N]",
+          "Remove directories from stack.", &[
+          "Removes the top entry, or the N-th entry, of the directory stack",
+          "and changes to the new top directory.",
+          "",
+          "Options:",
+          "  -n\tdo not change the directory",
+    ]),
+    topic("printf", "printf [-v var] format [arguments]",
+          "Formats and prints ARGUMENTS under control of the FORMAT.", &[
+          "FORMAT is reused until all ARGUMENTS are consumed.",
+          "",
+          "Options:",
+          "  -v\tassign the output to VAR instead of printing it",
+    ]),
+    topic("pushd", "pushd [-n] [+N | -N | dir]",
+          "Add directories to stack.", &[
+          "Pushes DIR onto the directory stack and changes to it. With +N or",
+          "-N, rotates the stack so that the N-th entry comes to the top.",
+          "Without arguments, swaps the top two entries.",
+          "",
+          "Options:",
+          "  -n\tdo not change the directory",
+    ]),
+    topic("pwd", "pwd [-LP]",
+          "Print the name of the current working directory.", &[
+          "Options:",
+          "  -L\tprint $PWD if it names the current directory",
+          "  -P\tprint the physical directory without symbolic links",
+    ]),
+    topic("read", "read [-ers] [-a array] [-d delim] [-i text] [-n nchars] [-N nchars] [-p prompt] [-t timeout] [-u fd] [name ...]",
+          "Read a line from the standard input and split it into fields.", &[
+          "Assigns the fields split by IFS to each NAME, or the line to REPLY",
+          "without NAME.",
+          "",
+          "Options:",
+          "  -a\tassign the fields to ARRAY",
+          "  -d\tread until DELIM instead of newline",
+          "  -r\tdo not treat backslashes as escapes",
+    ]),
+    topic("readarray", "readarray [-d delim] [-n count] [-O origin] [-s count] [-t] [-u fd] [-C callback] [-c quantum] [array]",
+          "Read lines from a file into an array variable.", &[
+          "A synonym for `mapfile'.",
+    ]),
+    topic("return", "return [n]",
+          "Return from a shell function.", &[
+          "Returns from a function or a sourced script with the status N,",
+          "or with the last status without N.",
+    ]),
+    topic("set", "set [-abefhkmnptuvxBCEHPT] [-o option-name] [--] [-] [arg ...]",
+          "Set or unset values of shell options and positional parameters.", &[
+          "Turns on the option with `-' and off with `+'. The rest of the",
+          "arguments are assigned to the positional parameters.",
+          "Without arguments, prints the shell variables.",
+          "With `set -b' (notify), the end of a job is reported at once.",
+    ]),
+    topic("shift", "shift [n]",
+          "Shift positional parameters.", &[
+          "Renames $N+1, $N+2, ... to $1, $2, .... N defaults to 1.",
+    ]),
+    topic("shopt", "shopt [-pqsu] [-o] [optname ...]",
+          "Set and unset shell options.", &[
+          "Prints the state of each OPTNAME, or of all the options.",
+          "",
+          "Options:",
+          "  -o\trestrict OPTNAMEs to those defined for use with `set -o'",
+          "  -p\tprint each shell option with an indication of its status",
+          "  -q\tsuppress output",
+          "  -s\tenable OPTNAME",
+          "  -u\tdisable OPTNAME",
+          "",
+          "Exits with 0 if every OPTNAME is enabled, otherwise 1.",
+    ]),
+    topic("source", "source filename [arguments]",
+          "Execute commands from a file in the current shell.", &[
+          "A synonym for `.'.",
+    ]),
+    topic("suspend", "suspend [-f]",
+          "Suspend shell execution.", &[
+          "Stops the shell until it receives SIGCONT.",
+          "",
+          "Options:",
+          "  -f\tsuspend even a login shell",
+    ]),
+    topic("times", "times",
+          "Display process times.", &[
+          "Prints the user and system times used by the shell and by its",
+          "children.",
+    ]),
+    topic("trap", "trap [-lp] [[arg] signal_spec ...]",
+          "Trap signals and other events.", &[
+          "Runs ARG when the shell receives SIGNAL_SPEC.",
+          "EXIT runs ARG at the exit of the shell, ERR after a failed command,",
+          "DEBUG before each simple command and RETURN after a function or",
+          "a sourced file. Functions inherit ERR with `set -E' and DEBUG and",
+          "RETURN with `set -T'.",
+    ]),
+    topic("true", "true",
+          "Return a successful result.", &[
+          "Always succeeds.",
+    ]),
+    topic("type", "type [-afptP] name [name ...]",
+          "Display information about command type.", &[
+          "Prints how each NAME would be interpreted as a command.",
+          "",
+          "Options:",
+          "  -a\tprint all the places that contain NAME",
+          "  -f\tskip functions",
+          "  -P\tsearch only PATH",
+          "  -p\tprint the file to be run",
+          "  -t\tprint a single word: alias, keyword, function, builtin or file",
+    ]),
+    topic("ulimit", "ulimit [-SHabcdefiklmnpqrstuvxPRT] [limit]",
+          "Modify shell resource limits.", &[
+          "Prints or sets the resource limit of each option. LIMIT is a",
+          "number, `unlimited', `soft' or `hard'. Without options, -f is used.",
+          "",
+          "Options:",
+          "  -S\tuse the soft limit",
+          "  -H\tuse the hard limit",
+          "  -a\tprint all the limits",
+    ]),
+    topic("umask", "umask [-p] [-S] [mode]",
+          "Display or set file mode mask.", &[
+          "Sets the file creation mask to MODE given in octal or symbolically.",
+          "Without MODE, prints the current mask.",
+          "",
+          "Options:",
+          "  -p\tprint in a reusable format",
+          "  -S\tprint symbolically",
+    ]),
+    topic("unalias", "unalias [-a] name [name ...]",
+          "Remove each NAME from the list of defined aliases.", &[
+          "Options:",
+          "  -a\tremove all aliases",
+    ]),
+    topic("unset", "unset [-f] [-v] [-n] [name ...]",
+          "Unset values and attributes of shell variables and functions.", &[
+          "Options:",
+          "  -f\ttreat each NAME as a function",
+          "  -v\ttreat each NAME as a variable",
+    ]),
+    topic("wait", "wait [-fn] [-p var] [id ...]",
+          "Wait for job completion and return exit status.", &[
+          "Waits for each process or job ID, or for all the children",
+          "without ID.",
+          "",
+          "Options:",
+          "  -n\twait for a single job to finish",
+          "  -p\tassign the ID of the finished job to VAR",
+    ]),
+];
+
+pub fn find(name: &str) -> Option<&'static Topic> {
+    TOPICS.iter().find(|t| t.name == name)
+}
+
+pub fn print_usage(name: &str) {
+    if let Some(t) = find(name) {
+        eprintln!("{}: usage: {}", name, t.synopsis);
+    }
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(|c| "*?[".contains(c))
+}
+
+/* patterns without glob characters match the heads of the names */
+fn matches(topic: &Topic, pattern: &str) -> bool {
+    match is_glob(pattern) {
+        true  => glob::parse_and_compare(&topic.name.to_string(), pattern, false),
+        false => topic.name.starts_with(pattern),
+    }
+}
+
+fn print_long(t: &Topic) {
+    println!("{}: {}", t.name, t.synopsis);
+    println!("    {}", t.short);
+    println!("    ");
+    t.long.iter().for_each(|line| println!("    {}", line));
+}
+
+fn print_manpage(t: &Topic) {
+    println!("NAME\n    {} - {}\n", t.name, t.short);
+    println!("SYNOPSIS\n    {}\n", t.synopsis);
+    println!("DESCRIPTION\n    {}\n    ", t.short);
+    t.long.iter().for_each(|line| println!("    {}", line));
+    println!();
+}
+
+fn print_list(core: &mut ShellCore) {
+    const V: &str = env!("CARGO_PKG_VERSION");
+    const P: &str = env!("CARGO_BUILD_PROFILE");
+    println!("Rusty Bash (a.k.a. Sushi shell), version {} - {}", V, P);
+    println!("These shell commands are defined internally.  Type `help' to see this list.");
+    println!("Type `help name' to find out more about the function `name'.");
+    println!("Use `man -k' or `info' to find out more about commands not in this list.");
+    println!();
+    println!("A star (*) next to a name means that the command is disabled.");
+    println!();
+
+    let entries: Vec<String> = TOPICS.iter().map(|t| {
+        let mark = match core.disabled_builtins.contains_key(t.name) {
+            true  => "*",
+            false => " ",
+        };
+        let s = format!("{}{}", mark, t.synopsis);
+        match s.chars().count() > 37 {
+            true  => s.chars().take(37).collect::<String>() + ">",
+            false => s,
+        }
+    }).collect();
+
+    let half = entries.len().div_ceil(2);
+    for i in 0..half {
+        match entries.get(i + half) {
+            Some(right) => println!("{:<40}{}", entries[i], right),
+            None        => println!("{}", entries[i]),
+        }
+    }
+}
+
+pub fn help(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, patterns) = match arg::getopt(&args[1..], "dms") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("help: {}", msg), core);
+            print_usage("help");
+            return 2;
+        },
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    if patterns.is_empty() {
+        print_list(core);
+        return 0;
+    }
+
+    let topics: Vec<&Topic> = TOPICS.iter()
+                              .filter(|t| patterns.iter().any(|p| matches(t, p)))
+                              .collect();
+
+    if topics.is_empty() {
+        let p = patterns.join(" ");
+        let msg = format!("help: no help topics match `{}'.  Try `help help' or `man -k {}' or `info {}'.", &p, &p, &p);
+        error::print(&msg, core);
+        return 1;
+    }
+
+    if is_glob(&patterns[0]) {
+        let s = if patterns.len() > 1 { "s" } else { "" };
+        println!("Shell commands matching keyword{} `{}'\n", s, patterns.join(", "));
+    }
+
+    for t in topics {
+        if has('d') {
+            println!("{} - {}", t.name, t.short);
+        }else if has('s') {
+            println!("{}: {}", t.name, t.synopsis);
+        }else if has('m') {
+            print_manpage(t);
+        }else{
+            print_long(t);
+        }
+    }
+    0
+}
@@ -5,6 +5,8 @@ use crate::ShellCore;
 use crate::core::JobEntry;
 use crate::signal;
 use crate::error;
+use crate::error::exec::ExecError;
+use crate::utils::arg;
 use nix::sys::signal::Signal;
 use nix::unistd;
 use nix::unistd::Pid;
@@ -19,49 +21,53 @@ fn id_to_job(id: usize, jobs: &mut Vec<JobEntry>) -> Option<&mut JobEntry> {
     None
 }
 
+/* %%, %+ and %- follow the priority; %n is a job number,
+ * %?str is a job containing str and %str is a job starting with str */
 fn arg_to_id(s: &str, priority: &Vec<usize>, table: &Vec<JobEntry>) -> Result<usize, String> {
-    if s == "%+" {
-        return match priority.len() {
-            0 => Err("%+: no such job".to_string()), 
-            _ => Ok(priority[0]),
-        };
+    let no_such_job = || format!("{}: no such job", s);
+    let word = match s.strip_prefix("%") {
+        Some(w) => w,
+        None => return Err(no_such_job()),
+    };
+
+    match word {
+        "" | "%" | "+" => return priority.first().copied().ok_or_else(no_such_job),
+        "-" => return priority.get(1).or(priority.first()).copied().ok_or_else(no_such_job),
+        _ => {},
     }
 
-    if s == "%-" {
-        return match priority.len() {
-            0 => Err("%-: no such job".to_string()), 
-            1 => Err("%-: no such job".to_string()), 
-            _ => Ok(priority[1]),
+    if let Ok(n) = word.parse::<usize>() {
+        return match table.iter().any(|j| j.id == n) {
+            true  => Ok(n),
+            false => Err(no_such_job()),
         };
     }
 
-    let word = &s[1..];
-    let mut ans = 0;
-    for job in table {
-        let jobname = job.text.split(" ").nth(0).unwrap();
-        if jobname == word {
-            if ans != 0 {
-                return Err((s.to_owned() + ": ambiguous job spec").to_string());
-            }
-            ans = job.id;
-        }
-    }
+    let (key, matched): (&str, Vec<usize>) = match word.strip_prefix("?") {
+        Some(w) => (w, table.iter().filter(|j| j.text.contains(w)).map(|j| j.id).collect()),
+        None    => (word, table.iter().filter(|j| j.text.trim_start().starts_with(word)).map(|j| j.id).collect()),
+    };
 
-    if ans != 0 {
-        return Ok(ans);
+    match matched.len() {
+        0 => Err(no_such_job()),
+        1 => Ok(matched[0]),
+        _ => Err(format!("{}: ambiguous job spec", key)),
     }
+}
 
-    if s.starts_with("%") {
-        return match word.parse::<usize>() {
-            Ok(n)  => Ok(n),
-            Err(_) => Err((s.to_owned() + ": no such job").to_string()),
-        };
+fn no_job_control(core: &mut ShellCore, com: &str) -> bool {
+    if core.db.flags.contains('m') {
+        return false;
     }
-
-    Err((s.to_owned() + ": no such job").to_string())
+    error::print(&format!("{}: no job control", com), core);
+    true
 }
 
 pub fn bg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if no_job_control(core, "bg") {
+        return 1;
+    }
+
     let id = if args.len() == 1 {
         if core.job_table_priority.is_empty() {
             return 1;
@@ -87,6 +93,10 @@ pub fn bg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
 }
 
 pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if no_job_control(core, "fg") {
+        return 1;
+    }
+
     let fd = match core.tty_fd.as_ref() {
         Some(fd) => fd,
         _        => return 1,
@@ -125,7 +135,12 @@ pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if let Ok(_) =  unistd::tcsetpgrp(fd, pgid) {
         eprintln!("{}", &job.text);
         job.send_cont();
-        exit_status = job.update_status(true).unwrap_or(1);
+        exit_status = loop { // traps wait for the job
+            match job.update_status(true) {
+                Err(ExecError::Interrupted) => continue,
+                status => break status.unwrap_or(1),
+            }
+        };
 
         if let Ok(mypgid) = unistd::getpgid(Some(Pid::from_raw(0))) {
             let _ = unistd::tcsetpgrp(fd, mypgid);
@@ -136,38 +151,423 @@ pub fn fg(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     exit_status
 }
 
-pub fn jobs(core: &mut ShellCore, _: &mut Vec<String>) -> i32 {
-    for job in core.job_table.iter() {
-        job.print(&core.job_table_priority);
+fn jobspecs_to_ids(core: &mut ShellCore, com: &str, specs: &[String]) -> Result<Vec<usize>, i32> {
+    if specs.is_empty() {
+        return Ok(core.job_table.iter().map(|j| j.id).collect());
+    }
+
+    let mut ans = vec![];
+    for spec in specs {
+        match arg_to_id(spec, &core.job_table_priority, &core.job_table) {
+            Ok(id) if core.job_table.iter().any(|j| j.id == id) => ans.push(id),
+            Ok(_) => {
+                error::print(&format!("{}: {}: no such job", com, spec), core);
+                return Err(1);
+            },
+            Err(s) => {
+                error::print(&format!("{}: {}", com, s), core);
+                return Err(1);
+            },
+        }
+    }
+    Ok(ans)
+}
+
+fn jobs_execute(core: &mut ShellCore, words: &[String]) -> i32 {
+    let mut words = words.to_vec();
+    for w in words.iter_mut().filter(|w| w.starts_with("%")) {
+        let id = match arg_to_id(w, &core.job_table_priority, &core.job_table) {
+            Ok(id) => id,
+            Err(s) => {
+                error::print(&format!("jobs: {}", s), core);
+                return 1;
+            },
+        };
+        match id_to_job(id, &mut core.job_table) {
+            Some(job) => *w = job.pids()[0].to_string(),
+            None => {
+                error::print(&format!("jobs: {}: no such job", w), core);
+                return 1;
+            },
+        }
+    }
+
+    match words.is_empty() {
+        true  => 0,
+        false => super::run_command(core, words),
+    }
+}
+
+pub fn jobs(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, specs) = match arg::getopt(&args[1..], "lnprsx") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("jobs: {}", msg), core);
+            super::help::print_usage("jobs");
+            return 2;
+        },
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    if has('x') {
+        return jobs_execute(core, &specs);
+    }
+
+    if let Err(e) = core.jobtable_check_status() {
+        e.print(core);
+    }
+
+    let ids = match jobspecs_to_ids(core, "jobs", &specs) {
+        Ok(ids) => ids,
+        Err(status) => return status,
+    };
+
+    for job in core.job_table.iter_mut().filter(|j| ids.contains(&j.id)) {
+        if (has('r') && job.is_stopped())
+        || (has('s') && ! job.is_stopped())
+        || (has('n') && ! job.change) {
+            continue;
+        }
+
+        match has('p') {
+            true  => println!("{}", job.pids()[0]),
+            false => job.print(&core.job_table_priority, has('l')),
+        }
+        job.change = false;
     }
     0
 }
 
-pub fn wait(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
-        for job in core.job_table.iter_mut() {
-            if let Err(e) = job.update_status(true) {
-                e.print(core);
+pub fn disown(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, specs) = match arg::getopt(&args[1..], "ahr") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("disown: {}", msg), core);
+            super::help::print_usage("disown");
+            return 2;
+        },
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    let ids = if has('a') || has('r') {
+        core.job_table.iter().filter(|j| ! has('r') || ! j.is_stopped())
+                             .map(|j| j.id).collect()
+    }else if specs.is_empty() {
+        match core.job_table_priority.first() {
+            Some(id) => vec![*id],
+            None => {
+                error::print("disown: current: no such job", core);
                 return 1;
+            },
+        }
+    }else{
+        match jobspecs_to_ids(core, "disown", &specs) {
+            Ok(ids) => ids,
+            Err(status) => return status,
+        }
+    };
+
+    if has('h') {
+        core.job_table.iter_mut().filter(|j| ids.contains(&j.id))
+                                 .for_each(|j| j.no_hup = true);
+    }else{
+        core.job_table.retain(|j| ! ids.contains(&j.id));
+        core.job_table_priority.retain(|id| ! ids.contains(id));
+    }
+    0
+}
+
+pub fn suspend(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let force = match arg::getopt(&args[1..], "f") {
+        Ok((opts, _)) => ! opts.is_empty(),
+        Err(msg) => {
+            error::print(&format!("suspend: {}", msg), core);
+            super::help::print_usage("suspend");
+            return 2;
+        },
+    };
+
+    if ! core.db.flags.contains('m') {
+        error::print("suspend: cannot suspend: no job control", core);
+        return 1;
+    }
+
+    let login = core.db.get_param("0").unwrap_or_default().starts_with("-")
+                || core.options.query("login_shell");
+    if login && ! force {
+        error::print("suspend: cannot suspend a login shell", core);
+        return 1;
+    }
+
+    let _ = nix::sys::signal::kill(unistd::getpid(), Signal::SIGSTOP);
+    0
+}
+
+fn pid_to_id(pid: &str, table: &[JobEntry]) -> Option<usize> {
+    let pid = pid.parse::<i32>().ok()?;
+    table.iter().find(|j| j.pids().iter().any(|p| p.as_raw() == pid)).map(|j| j.id)
+}
+
+fn wait_job(core: &mut ShellCore, id: usize, force: bool) -> Result<i32, ExecError> {
+    let job = match id_to_job(id, &mut core.job_table) {
+        Some(job) => job,
+        None => return Ok(127),
+    };
+
+    job.update_status(true)?;
+    while force && job.is_stopped() {
+        job.update_status(true)?;
+    }
+
+    let status = job.exit_status();
+    if job.is_finished() {
+        remove_job(core, id);
+    }
+    Ok(status)
+}
+
+/* a job whose status has been collected by wait is no longer in the table */
+fn remove_job(core: &mut ShellCore, id: usize) {
+    core.job_table.retain(|j| j.id != id);
+    core.job_table_priority.retain(|i| *i != id);
+}
+
+fn wait_next(core: &mut ShellCore, ids: &[usize]) -> Result<Option<usize>, ExecError> {
+    loop {
+        let mut waiting = false;
+        for job in core.job_table.iter_mut().filter(|j| ids.contains(&j.id)) {
+            job.update_status(false)?;
+            if job.is_finished() {
+                return Ok(Some(job.id));
             }
+            waiting = true;
         }
-        return 0;
+
+        if ! waiting {
+            return Ok(None);
+        }
+        if signal::caught().is_some() {
+            return Err(ExecError::Interrupted);
+        }
+        signal::pause(core);
     }
+}
 
-    let id = match arg_to_id(&args[1], &core.job_table_priority, &core.job_table) {
-        Ok(n)  => n,
-        Err(s) => {
-            error::print(&("wait: ".to_owned() + &s), core);
-            return 1;
+fn wait_set_var(core: &mut ShellCore, var: &Option<String>, id: usize) -> Result<(), ExecError> {
+    let name = match var {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+
+    let pid = match id_to_job(id, &mut core.job_table) {
+        Some(job) => job.pids().last().map(|p| p.to_string()).unwrap_or_default(),
+        None => return Ok(()),
+    };
+    core.db.set_param(name, &pid, None)
+}
+
+fn wait_args_to_ids(core: &mut ShellCore, args: &[String]) -> Vec<Result<usize, String>> {
+    let mut ans = vec![];
+    for a in args {
+        if a.starts_with("%") {
+            ans.push(match arg_to_id(a, &core.job_table_priority, &core.job_table) {
+                Ok(id) if core.job_table.iter().any(|j| j.id == id) => Ok(id),
+                Ok(_)  => Err(format!("wait: {}: no such job", a)),
+                Err(s) => Err(format!("wait: {}", s)),
+            });
+        }else if let Some(id) = pid_to_id(a, &core.job_table) {
+            ans.push(Ok(id));
+        }else if a.parse::<i32>().is_ok() {
+            ans.push(Err(format!("wait: pid {} is not a child of this shell", a)));
+        }else{
+            ans.push(Err(format!("wait: `{}': not a pid or valid job spec", a)));
+        }
+    }
+    ans
+}
+
+fn wait_main(core: &mut ShellCore, args: &[String]) -> Result<i32, ExecError> {
+    let (opts, specs) = match arg::getopt(args, "fnp:") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("wait: {}", msg), core);
+            super::help::print_usage("wait");
+            return Ok(2);
         },
     };
-    match id_to_job(id, &mut core.job_table) {
-        Some(job) => if let Err(e) = job.update_status(true) {
+    let force = opts.iter().any(|o| o.0 == 'f');
+    let next = opts.iter().any(|o| o.0 == 'n');
+    let var = opts.iter().find(|o| o.0 == 'p').and_then(|o| o.1.clone());
+    if let Some(name) = &var {
+        core.db.unset(name);
+    }
+
+    let ids = wait_args_to_ids(core, &specs);
+
+    if next {
+        let mut candidates = vec![];
+        for id in ids {
+            match id {
+                Ok(id) => candidates.push(id),
+                Err(msg) => error::print(&msg, core),
+            }
+        }
+        if specs.is_empty() {
+            candidates = core.job_table.iter().map(|j| j.id).collect();
+        }
+
+        return match wait_next(core, &candidates)? {
+            Some(id) => {
+                wait_set_var(core, &var, id)?;
+                let status = id_to_job(id, &mut core.job_table).unwrap().exit_status();
+                remove_job(core, id);
+                Ok(status)
+            },
+            None => Ok(127),
+        };
+    }
+
+    if specs.is_empty() {
+        for job in core.job_table.iter_mut() {
+            job.update_status(true)?;
+        }
+        let finished = core.job_table.iter().filter(|j| j.is_finished())
+                       .map(|j| j.id).collect::<Vec<usize>>();
+        for id in finished {
+            remove_job(core, id);
+        }
+        return Ok(0);
+    }
+
+    let mut exit_status = 0;
+    for id in ids {
+        exit_status = match id {
+            Ok(id) => {
+                wait_set_var(core, &var, id)?;
+                wait_job(core, id, force)?
+            },
+            Err(msg) => {
+                error::print(&msg, core);
+                127
+            },
+        };
+    }
+    Ok(exit_status)
+}
+
+pub fn wait(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    match wait_main(core, &args[1..]) {
+        Ok(status) => status,
+        Err(ExecError::Interrupted) => 128 + signal::caught().unwrap_or(Signal::SIGINT as i32),
+        Err(e) => {
             e.print(core);
-            return 1;
+            1
+        },
+    }
+}
+
+fn kill_usage() -> i32 {
+    super::help::print_usage("kill");
+    2
+}
+
+fn kill_list(core: &mut ShellCore, args: &[String]) -> i32 {
+    if args.is_empty() {
+        signal::print_signal_list();
+        return 0;
+    }
+
+    let mut exit_status = 0;
+    for a in args {
+        let ans = match a.parse::<i32>() {
+            Ok(n) if n > 128 => signal::num_to_name(n - 128),
+            Ok(n) => signal::num_to_name(n),
+            _ => signal::name_to_num(a).map(|n| n.to_string()),
+        };
+
+        match ans {
+            Some(s) => println!("{}", s),
+            None => {
+                error::print(&format!("kill: {}: invalid signal specification", a), core);
+                exit_status = 1;
+            },
         }
-        _ => return 1, 
     }
+    exit_status
+}
 
-    0
+fn kill_target(core: &mut ShellCore, target: &str, sig: i32) -> Result<(), String> {
+    if target.starts_with("%") {
+        let id = arg_to_id(target, &core.job_table_priority, &core.job_table)?;
+        return match id_to_job(id, &mut core.job_table) {
+            Some(job) => job.send_signal(sig)
+                            .map_err(|e| format!("{}: {}", target, String::from(e))),
+            None => Err(format!("{}: no such job", target)),
+        };
+    }
+
+    let pid = match target.parse::<i32>() {
+        Ok(pid) => pid,
+        _ => return Err(format!("{}: arguments must be process or job IDs", target)),
+    };
+
+    match unsafe { nix::libc::kill(pid, sig) } {
+        0 => Ok(()),
+        _ => Err(format!("({}) - {}", pid, nix::errno::Errno::last().desc())),
+    }
+}
+
+pub fn kill(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() <= 1 {
+        return kill_usage();
+    }
+
+    let mut sig = signal::name_to_num("TERM").unwrap();
+    let mut pos = 1;
+    while pos < args.len() && args[pos].starts_with("-") {
+        let (spec, next) = match args[pos].as_str() {
+            "--" => {
+                pos += 1;
+                break;
+            },
+            "-l" | "-L" => return kill_list(core, &args[pos+1..]),
+            "-s" | "-n" => match args.get(pos+1) {
+                Some(s) => (s.clone(), pos + 2),
+                None => {
+                    let msg = format!("kill: {}: option requires an argument", &args[pos]);
+                    error::print(&msg, core);
+                    return 1;
+                },
+            },
+            opt => {
+                let spec = opt[1..].to_string();
+                if spec.parse::<i32>().is_ok() && pos > 1 { // negative pid
+                    break;
+                }
+                (spec, pos + 1)
+            },
+        };
+
+        sig = match signal::name_to_num(&spec) {
+            Some(n) => n,
+            None => {
+                error::print(&format!("kill: {}: invalid signal specification", spec), core);
+                return 1;
+            },
+        };
+        pos = next;
+    }
+
+    if pos >= args.len() {
+        return kill_usage();
+    }
+
+    let mut exit_status = 1;
+    for target in &args[pos..] {
+        match kill_target(core, target, sig) {
+            Ok(()) => exit_status = 0,
+            Err(msg) => error::print(&format!("kill: {}", msg), core),
+        }
+    }
+    exit_status
 }
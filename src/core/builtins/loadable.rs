@@ -0,0 +1,202 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{file_check, ShellCore};
+use nix::libc;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::io::{stderr, stdout, Write};
+use std::ptr;
+use sush_plugin_api::{AbiVersionFn, CallFn, HasFn, Host};
+
+#[derive(Clone)]
+pub struct Loadable {
+    pub path: String,
+    handle: usize,
+    call: CallFn,
+}
+
+fn dlerror() -> String {
+    let e = unsafe { libc::dlerror() };
+    match e.is_null() {
+        true  => "unknown error".to_string(),
+        false => unsafe { CStr::from_ptr(e) }.to_string_lossy().to_string(),
+    }
+}
+
+fn symbol(handle: *mut c_void, name: &str, file: &str) -> Result<*mut c_void, String> {
+    let cname = CString::new(name).unwrap();
+    let sym = unsafe { libc::dlsym(handle, cname.as_ptr()) };
+    match sym.is_null() {
+        true  => Err(format!("cannot find {} in shared object {}: {}", name, file, dlerror())),
+        false => Ok(sym),
+    }
+}
+
+/* a file name without slash is searched in BASH_LOADABLES_PATH */
+fn find_file(core: &mut ShellCore, file: &str) -> String {
+    if file.contains('/') {
+        return file.to_string();
+    }
+
+    let dirs = core.db.get_param("BASH_LOADABLES_PATH").unwrap_or_default();
+    for dir in dirs.split(':').filter(|d| ! d.is_empty()) {
+        let path = format!("{}/{}", dir.trim_end_matches('/'), file);
+        if file_check::exists(&path) {
+            return path;
+        }
+    }
+    file.to_string()
+}
+
+fn open(file: &str, path: &str) -> Result<*mut c_void, String> {
+    let cpath = CString::new(path).map_err(|e| e.to_string())?;
+    let handle = unsafe { libc::dlopen(cpath.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+    if handle.is_null() {
+        return Err(format!("cannot open shared object {}: {}", file, dlerror()));
+    }
+
+    let version = match symbol(handle, sush_plugin_api::ABI_VERSION_SYMBOL, file) {
+        Ok(sym) => unsafe { std::mem::transmute::<*mut c_void, AbiVersionFn>(sym)() },
+        Err(e) => {
+            unsafe { libc::dlclose(handle) };
+            return Err(e);
+        },
+    };
+
+    if version != sush_plugin_api::ABI_VERSION {
+        unsafe { libc::dlclose(handle) };
+        return Err(format!("{}: plugin ABI version {} is not supported (expected {})",
+                           file, version, sush_plugin_api::ABI_VERSION));
+    }
+    Ok(handle)
+}
+
+fn close_if_unused(core: &mut ShellCore, handle: usize) {
+    if ! core.loadables.values().any(|l| l.handle == handle) {
+        unsafe { libc::dlclose(handle as *mut c_void) };
+    }
+}
+
+/* registers the builtins in the shared object and returns the error messages */
+pub fn load(core: &mut ShellCore, file: &str, names: &[String]) -> Vec<String> {
+    let path = find_file(core, file);
+    let handle = match open(file, &path) {
+        Ok(h) => h,
+        Err(e) => return vec![e],
+    };
+    let already_loaded = core.loadables.values().any(|l| l.handle == handle as usize);
+
+    let syms = symbol(handle, sush_plugin_api::HAS_SYMBOL, file)
+               .and_then(|h| symbol(handle, sush_plugin_api::CALL_SYMBOL, file).map(|c| (h, c)));
+    let (has, call) = match syms {
+        Ok((h, c)) => unsafe {
+            (std::mem::transmute::<*mut c_void, HasFn>(h),
+             std::mem::transmute::<*mut c_void, CallFn>(c))
+        },
+        Err(e) => {
+            unsafe { libc::dlclose(handle) };
+            return vec![e];
+        },
+    };
+
+    let mut errors = vec![];
+    for name in names {
+        let cname = CString::new(name.as_str()).unwrap_or_default();
+        if unsafe { has(cname.as_ptr()) } == 0 {
+            errors.push(format!("cannot find {} in shared object {}", name, file));
+            continue;
+        }
+
+        let loadable = Loadable { path: path.clone(), handle: handle as usize, call };
+        if let Some(old) = core.loadables.insert(name.clone(), loadable) {
+            close_if_unused(core, old.handle);
+        }
+        core.disabled_builtins.remove(name);
+        core.builtins.insert(name.clone(), run);
+    }
+
+    if already_loaded || ! core.loadables.values().any(|l| l.handle == handle as usize) {
+        unsafe { libc::dlclose(handle) }; // one reference is kept for each shared object
+    }
+    errors
+}
+
+pub fn unload(core: &mut ShellCore, name: &str) -> bool {
+    let loadable = match core.loadables.remove(name) {
+        Some(l) => l,
+        None    => return false,
+    };
+
+    core.builtins.remove(name);
+    core.disabled_builtins.remove(name);
+    close_if_unused(core, loadable.handle);
+    true
+}
+
+fn to_str(s: *const c_char) -> String {
+    unsafe { CStr::from_ptr(s) }.to_string_lossy().to_string()
+}
+
+unsafe extern "C" fn get_var(core: *mut c_void, name: *const c_char) -> *mut c_char {
+    let core = &mut *(core as *mut ShellCore);
+    let name = to_str(name);
+    if ! core.db.has_value(&name) {
+        return ptr::null_mut();
+    }
+
+    match core.db.get_param(&name).ok().and_then(|v| CString::new(v).ok()) {
+        Some(v) => v.into_raw(),
+        None    => ptr::null_mut(),
+    }
+}
+
+unsafe extern "C" fn set_var(core: *mut c_void, name: *const c_char, value: *const c_char) -> c_int {
+    let core = &mut *(core as *mut ShellCore);
+    match core.db.set_param(&to_str(name), &to_str(value), None) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    }
+}
+
+unsafe extern "C" fn free_string(s: *mut c_char) {
+    if ! s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+unsafe extern "C" fn write(fd: c_int, buf: *const u8, len: usize) -> isize {
+    let buf = std::slice::from_raw_parts(buf, len);
+    let res = match fd {
+        2 => stderr().write_all(buf),
+        _ => stdout().write_all(buf).and_then(|_| stdout().flush()),
+    };
+    match res {
+        Ok(()) => len as isize,
+        Err(_) => -1,
+    }
+}
+
+pub fn run(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let call = match core.loadables.get(&args[0]) {
+        Some(l) => l.call,
+        None    => return 1,
+    };
+
+    let cargs: Vec<CString> = args.iter()
+                              .map(|a| CString::new(a.as_str()).unwrap_or_default())
+                              .collect();
+    let mut argv: Vec<*const c_char> = cargs.iter().map(|a| a.as_ptr()).collect();
+    argv.push(ptr::null());
+
+    let host = Host {
+        abi_version: sush_plugin_api::ABI_VERSION,
+        core: core as *mut ShellCore as *mut c_void,
+        get_var,
+        set_var,
+        free_string,
+        write,
+    };
+
+    let _ = stdout().flush();
+    unsafe { call(&host, cargs.len() as c_int, argv.as_ptr()) }
+}
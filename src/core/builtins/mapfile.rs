@@ -0,0 +1,144 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, utils, ShellCore};
+use crate::utils::arg;
+use crate::utils::fd_reader::{FdReader, ReadError};
+use super::printf;
+use nix::fcntl;
+use std::os::fd::RawFd;
+
+struct Options {
+    delim: u8,
+    count: usize,
+    origin: Option<usize>,
+    skip: usize,
+    strip: bool,
+    fd: RawFd,
+    callback: Option<String>,
+    quantum: usize,
+    name: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            delim: b'\n',
+            count: 0,
+            origin: None,
+            skip: 0,
+            strip: false,
+            fd: 0,
+            callback: None,
+            quantum: 5000,
+            name: "MAPFILE".to_string(),
+        }
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let (opts, names) = arg::getopt(args, "d:n:O:s:tu:C:c:")?;
+    let mut ans = Options::default();
+
+    let to_num = |v: &str, kind: &str| match v.parse::<usize>() {
+        Ok(n) => Ok(n),
+        _     => Err(format!("{}: invalid {}", v, kind)),
+    };
+
+    for (opt, value) in opts {
+        let value = value.unwrap_or_default();
+        match opt {
+            'd' => ans.delim = *value.as_bytes().first().unwrap_or(&0),
+            'n' => ans.count = to_num(&value, "line count")?,
+            'O' => ans.origin = Some(to_num(&value, "array origin")?),
+            's' => ans.skip = to_num(&value, "line count")?,
+            't' => ans.strip = true,
+            'u' => match value.parse::<RawFd>() {
+                Ok(fd) if fd >= 0 && fcntl::fcntl(fd, fcntl::F_GETFD).is_ok() => ans.fd = fd,
+                _ => return Err(format!("{}: invalid file descriptor: Bad file descriptor", &value)),
+            },
+            'C' => ans.callback = Some(value),
+            'c' => match to_num(&value, "callback quantum") {
+                Ok(n) if n > 0 => ans.quantum = n,
+                _ => return Err(format!("{}: invalid callback quantum", &value)),
+            },
+            _ => {},
+        }
+    }
+
+    if let Some(name) = names.first() {
+        if ! utils::is_param(name) {
+            return Err(format!("`{}': not a valid identifier", name));
+        }
+        ans.name = name.clone();
+    }
+
+    Ok(ans)
+}
+
+fn call_back(core: &mut ShellCore, callback: &str, index: usize, line: &str) {
+    let mut args = vec!["eval".to_string(), callback.to_string(),
+                        index.to_string(), printf::quote(line)];
+    super::eval(core, &mut args);
+}
+
+fn read_lines(core: &mut ShellCore, opts: &Options) -> Result<(), String> {
+    let mut reader = FdReader::new(opts.fd);
+    let mut index = opts.origin.unwrap_or(0);
+
+    if opts.origin.is_none() {
+        core.db.set_array(&opts.name, vec![], None)?;
+    }
+
+    for _ in 0..opts.skip {
+        if let Ok(None) | Err(_) = reader.line(opts.delim) {
+            return Ok(());
+        }
+    }
+
+    let mut num = 0;
+    while opts.count == 0 || num < opts.count {
+        let mut line = match reader.line(opts.delim) {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(ReadError::Errno(e)) => return Err(format!("read error: {}: {}", opts.fd, e.desc())),
+            Err(ReadError::Timeout) | Err(ReadError::Signal(_)) => break,
+        };
+
+        if opts.strip && line.last() == Some(&opts.delim) {
+            line.pop();
+        }
+        let line = String::from_utf8_lossy(&line).to_string();
+
+        num += 1;
+        if let Some(callback) = &opts.callback {
+            if num % opts.quantum == 0 {
+                call_back(core, callback, index, &line);
+            }
+        }
+
+        core.db.set_array_elem(&opts.name, &line, index, None)?;
+        index += 1;
+    }
+    Ok(())
+}
+
+pub fn mapfile(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let opts = match parse_options(&args[1..]) {
+        Ok(opts) => opts,
+        Err(msg) => {
+            error::print(&format!("{}: {}", &args[0], msg), core);
+            if ! msg.ends_with("invalid option") && ! msg.ends_with("requires an argument") {
+                return 1;
+            }
+            super::help::print_usage(&args[0]);
+            return 2;
+        },
+    };
+
+    if let Err(msg) = read_lines(core, &opts) {
+        error::print(&format!("{}: {}", &args[0], msg), core);
+        return 1;
+    }
+    0
+}
@@ -1,23 +1,74 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{error, ShellCore};
+use crate::{env, error, ShellCore};
+use crate::core::options::Options;
 use crate::error::exec::ExecError;
 use crate::utils::arg;
+use nix::unistd;
 use super::parameter;
 
-fn set_option(core: &mut ShellCore, opt: char, pm: char) {
-    if pm == '+' {
-        core.db.flags.retain(|e| e != opt);
-    }else{
-        if ! core.db.flags.contains(opt) {
-            core.db.flags.push(opt);
+fn set_option(core: &mut ShellCore, opt: char, pm: char) -> Result<(), ExecError> {
+    let name = core.options.name_of(opt).unwrap_or_default();
+    if pm == '-' && ! core.options.is_supported(&name) {
+        return Err(ExecError::Other(format!("set: -{}: not supported yet", opt)));
+    }
+    core.set_flag_option(opt, pm == '-');
+
+    if opt == 'p' && pm == '+' { // the effective ids go back to the real ones
+        let _ = unistd::setgid(unistd::getgid());
+        let _ = unistd::setuid(unistd::getuid());
+    }
+    Ok(())
+}
+
+fn set_long_option(core: &mut ShellCore, name: &str, pm: char) -> Result<(), ExecError> {
+    if ! core.options.exist(name, false) {
+        return Err(ExecError::Other(format!("set: {}: invalid option name", name)));
+    }
+    if pm == '-' && ! core.options.is_supported(name) {
+        return Err(ExecError::Other(format!("set: {}: not supported yet", name)));
+    }
+
+    match core.options.letter_of(name) {
+        Some(c) => set_option(core, c, pm),
+        None    => {core.set_option(name, pm == '-'); Ok(())},
+    }
+}
+
+/* -o lists the options in the format of shopt and +o in the format of set */
+pub fn print_set_options(core: &ShellCore, positive: bool) {
+    for opt in core.options.list(false) {
+        match positive {
+            true  => println!("{}", Options::format(&opt.name, opt.value)),
+            false => println!("{}", Options::format2(&opt)),
+        }
+    }
+}
+
+/* options in SHELLOPTS and BASHOPTS of the environment are turned on at startup */
+pub fn import_shellopts(core: &mut ShellCore) {
+    for name in env::var("SHELLOPTS").unwrap_or_default().split(':') {
+        let _ = set_long_option(core, name, '-');
+    }
+    for name in env::var("BASHOPTS").unwrap_or_default().split(':') {
+        if core.options.exist(name, true) {
+            core.set_option(name, true);
         }
     }
+    core.update_shellopts();
 }
 
+/* -o and +o take the name of an option from the next argument */
 pub fn set_options(core: &mut ShellCore, args: &[String]) -> Result<(), ExecError> {
-    for a in args {
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        if a == "-o" || a == "+o" {
+            let name = args.next().ok_or(ExecError::InvalidOption(a.to_string()))?;
+            set_long_option(core, name, a.chars().next().unwrap())?;
+            continue;
+        }
+
         if a.len() != 2 {
             return Err(ExecError::InvalidOption(a.to_string()));
         }
@@ -25,61 +76,86 @@ pub fn set_options(core: &mut ShellCore, args: &[String]) -> Result<(), ExecErro
         let pm = a.chars().nth(0).unwrap();
         let ch = a.chars().nth(1).unwrap();
 
-        if (pm != '-' && pm != '+') || "xveB".find(ch).is_none() {
+        if (pm != '-' && pm != '+') || core.options.name_of(ch).is_none() {
             return Err(ExecError::InvalidOption(a.to_string()));
         }
 
-        set_option(core, ch, pm);
+        set_option(core, ch, pm)?;
     }
     Ok(())
 }
 
-pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    let mut args = arg::dissolve_options(args);
+/* $0 is kept and the arguments are set as they are given */
+fn set_positions(core: &mut ShellCore, args: &[String]) -> Result<(), ExecError> {
+    let zero = core.db.position_parameters.last().map(|p| p[0].clone()).unwrap_or_default();
+    let mut positions = vec![zero];
+    positions.extend_from_slice(args);
+    parameter::set_positions(core, &positions)
+}
 
-    if args.len() <= 1 {
-        return parameter::print_all(core);
-    }
+/* options are read until "--", "-", or a word without a sign. The rest are
+ * the positional parameters. "-" also turns off -x and -v. */
+fn split_set_args(args: &[String]) -> (Vec<String>, Option<Vec<String>>) {
+    let mut opts = vec![];
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--" {
+            return (opts, Some(args[i+1..].to_vec()));
+        }
+        if a == "-" {
+            opts.extend(["+x".to_string(), "+v".to_string()]);
+            let rest = args[i+1..].to_vec();
+            return (opts, if rest.is_empty() {None} else {Some(rest)});
+        }
+        if a.len() < 2 || ! (a.starts_with("-") || a.starts_with("+")) {
+            return (opts, Some(args[i..].to_vec()));
+        }
 
-    if args[1].starts_with("--") {
-        args.remove(0);
-        return match parameter::set_positions(core, &args) {
-            Ok(()) => 0,
-            Err(e) => {
-                e.print(core);
-                return 1;
-            },
+        let pm = &a[..1];
+        for c in a[1..].chars() {
+            opts.push(format!("{}{}", pm, c));
+            if c == 'o' && i + 1 < args.len() {
+                i += 1;
+                opts.push(args[i].clone());
+            }
         }
+        i += 1;
     }
+    (opts, None)
+}
 
-    if args[1] == "-o" || args[1] == "+o" {
-        let positive = args[1] == "-o";
+pub fn set(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    if args.len() <= 1 {
+        return parameter::print_all(core);
+    }
 
-        if args.len() == 2 {
-            core.options.print_all(positive);
-            return 0;
-        }else{
-            /*
-            if args[2] == "noglob" {
-                eprintln!("{}: not supprted yet", &args[2]);
-                return 1;
-            }*/
-            return match core.options.set(&args[2], positive) {
-                true  => 0,
-                false => 2,
-            };
-        }
+    if args.len() == 2 && (args[1] == "-o" || args[1] == "+o") {
+        print_set_options(core, args[1] == "-o");
+        return 0;
     }
 
-    match args[1].starts_with("-") || args[1].starts_with("+") {
-        true  => if let Err(e) = set_options(core, &args[1..]) {
-            e.print(core);
+    let (opts, positions) = split_set_args(&args[1..]);
+    let result = set_options(core, &opts);
+    core.update_shellopts();
+    match result {
+        Err(ExecError::InvalidOption(opt)) => {
+            error::print(&format!("set: {}: invalid option", opt), core);
+            super::help::print_usage("set");
             return 2;
         },
-        false => if let Err(e) = parameter::set_positions(core, &args) {
+        Err(e) => {
             e.print(core);
             return 2;
         },
+        Ok(()) => {},
+    }
+
+    if let Some(positions) = positions {
+        if let Err(e) = set_positions(core, &positions) {
+            e.print(core);
+            return 2;
+        }
     }
     0
 }
@@ -125,72 +201,97 @@ pub fn shift(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     1
 }
 
-pub fn shopt_print(core: &mut ShellCore, args: &mut Vec<String>, all: bool) -> i32 {
-    if all {
-        core.shopts.print_all(true);
-        return 0;
-    }
+fn shopt_print(core: &mut ShellCore, names: &[String], shopt: bool,
+               has: impl Fn(char) -> bool) -> i32 {
+    let list = match names.is_empty() {
+        true  => core.options.list(shopt),
+        false => names.iter().filter_map(|n| core.options.get(n)).cloned().collect(),
+    };
 
-    let mut res = true;
-    match args[1].as_str() {
-        "-s" => core.shopts.print_if(true),
-        "-u" => core.shopts.print_if(false),
-        "-q" => return 0,
-        opt  => res = core.shopts.print_opt(opt),
-    }
+    let mut ans = 0;
+    for opt in list {
+        if (has('s') && ! opt.value) || (has('u') && opt.value) {
+            continue;
+        }
+        if ! opt.value && ! names.is_empty() {
+            ans = 1;
+        }
 
-    match res {
-        true  => 0,
-        false => 1,
+        if has('q') {
+            continue;
+        }
+        match has('p') {
+            true  => println!("{}", Options::format2(&opt)),
+            false => println!("{}", Options::format(&opt.name, opt.value)),
+        }
     }
+    ans
 }
 
-pub fn shopt(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    let mut args = arg::dissolve_options(args);
-
-    if args.contains(&"-p".to_string()) && args.contains(&"-o".to_string()) {
-            core.options.print_all(false);
-            return 0;
-    }
-
-    if args.len() < 3 {
-        let len = args.len();
-        return shopt_print(core, &mut args, len < 2);
-    }
+fn shopt_set(core: &mut ShellCore, names: &[String], shopt: bool, onoff: bool) -> i32 {
+    for name in names {
+        if onoff && ! core.options.is_supported(name) {
+            let msg = format!("shopt: {}: not supported yet", &name);
+            error::print(&msg, core);
+            return 1;
+        }
 
-    let res = match args[1].as_str() {
-        "-s" => {
-            if ["extglob", "progcomp"].iter().any(|&e| e == args[2]) {
-                core.shopts.set(&args[2], true)
-            }else{
-                let msg = format!("shopt: {}: not supported yet", &args[2]);
-                error::print(&msg, core);
+        if ! shopt {
+            if let Err(e) = set_long_option(core, name, if onoff {'-'} else {'+'}) {
+                e.print(core);
                 return 1;
             }
-        },
-        "-q" => {
-            for arg in &args[2..] {
-                if ! core.shopts.exist(arg) {
-                    let msg = format!("shopt: {}: invalid shell option name", &arg);
-                    error::print(&msg, core);
-                    return 1;
-                }
-                if ! core.shopts.query(arg) {
-                    return 1;
-                }
+            continue;
+        }
+        core.set_option(name, onoff);
+        if name == "extdebug" {
+            match onoff {
+                true  => core.update_bash_argv(),
+                false => ["BASH_ARGV", "BASH_ARGC"].iter().for_each(|a| core.db.unset_var(a)),
             }
-            return 0;
-        },
-        "-u" => core.shopts.set(&args[2], false),
-        arg  => {
-            eprintln!("sush: shopt: {}: invalid shell option name", arg);
-            eprintln!("shopt: usage: shopt [-su] [optname ...]");
-            false
+        }
+    }
+    0
+}
+
+pub fn shopt(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, names) = match arg::getopt(&args[1..], "opqsu") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("shopt: {}", msg), core);
+            super::help::print_usage("shopt");
+            return 2;
         },
     };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+    let shopt = ! has('o');
+
+    if has('s') && has('u') {
+        error::print("shopt: cannot set and unset shell options simultaneously", core);
+        return 1;
+    }
 
-    match res {
-        true  => 0,
-        false => 1,
+    let mut ans = 0;
+    let all = names.is_empty();
+    let (valid, invalid): (Vec<String>, Vec<String>) = names.into_iter()
+                          .partition(|n| core.options.exist(n, shopt));
+    for name in invalid {
+        let msg = match shopt {
+            true  => format!("shopt: {}: invalid shell option name", name),
+            false => format!("shopt: {}: invalid option name", name),
+        };
+        error::print(&msg, core);
+        ans = 1;
+    }
+    let names = valid;
+
+    if (has('s') || has('u')) && ! names.is_empty() {
+        if shopt_set(core, &names, shopt, has('s')) != 0 {
+            ans = 1;
+        }
+        core.update_shellopts();
+    }else if (all || ! names.is_empty()) && shopt_print(core, &names, shopt, has) != 0 {
+        ans = 1;
     }
+    ans
 }
@@ -1,129 +1,671 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
-use crate::error;
-use sprintf::PrintfError;
+use crate::{error, Feeder, ShellCore};
+use crate::elements::subscript::Subscript;
+use crate::error::exec::ExecError;
+use nix::libc;
+use std::{env, ptr};
+use std::ffi::CString;
 use std::io::{stdout, Write};
+use std::os::raw::c_char;
 
-fn split_format(format: &str) -> (Vec<String>, Option<String>) {
-    let mut escaped = false;
-    let mut percent = false;
-    let mut len = 0;
-    let mut len_prev = 0;
-    let mut ans = vec![];
+extern "C" {
+    fn tzset();
+}
 
-    for c in format.chars() {
-        if c == '\\' || escaped {
-            len += c.len_utf8();
-            escaped = ! escaped;
-            percent = false;
-            continue;
+enum NumError {
+    Invalid(i64),
+    OutOfRange(i64),
+}
+
+enum CValue {
+    Int(i64),
+    Float(f64),
+}
+
+struct Arguments {
+    list: Vec<String>,
+    pos: usize,
+    exit_status: i32,
+}
+
+impl Arguments {
+    fn next(&mut self) -> Option<String> {
+        let ans = self.list.get(self.pos).cloned();
+        if ans.is_some() {
+            self.pos += 1;
         }
+        ans
+    }
 
-        len += c.len_utf8();
-        if c == '%' {
-            percent = true;
-            continue;
+    fn next_str(&mut self) -> String {
+        self.next().unwrap_or_default()
+    }
+
+    fn next_int(&mut self, core: &mut ShellCore) -> i64 {
+        let arg = match self.next() {
+            Some(a) => a,
+            None    => return 0,
+        };
+
+        match to_int(&arg) {
+            Ok(n) => n,
+            Err(NumError::Invalid(n)) => {
+                error::print(&format!("printf: {}: invalid number", &arg), core);
+                self.exit_status = 1;
+                n
+            },
+            Err(NumError::OutOfRange(n)) => {
+                let msg = format!("printf: warning: {}: Numerical result out of range", &arg);
+                error::print(&msg, core);
+                n
+            },
         }
+    }
 
-        if percent {
-            ans.push(format[len_prev..len].to_string());
-            len_prev = len;
-            percent = false;
+    fn next_float(&mut self, core: &mut ShellCore) -> f64 {
+        let arg = match self.next() {
+            Some(a) => a,
+            None    => return 0.0,
+        };
+
+        match to_float(&arg) {
+            Ok(n) => n,
+            Err(n) => {
+                error::print(&format!("printf: {}: invalid number", &arg), core);
+                self.exit_status = 1;
+                n
+            },
         }
     }
 
-    match format[len_prev..len].is_empty() {
-        true  => (ans, None),
-        false => (ans, Some(format[len_prev..len].to_string()) ),
+    fn rest(&self) -> bool {
+        self.pos < self.list.len()
     }
 }
 
-fn output(pattern: &str, args: &mut Vec<String>) -> Result<String, PrintfError> {
-    let mut ans = String::new();
-    let (parts, tail) = split_format(&pattern);
+#[derive(Clone, Default)]
+struct Spec {
+    flags: String,
+    width: Option<i64>,
+    precision: Option<i64>,
+    conv: char,
+    datefmt: String,
+}
 
-    while parts.len() > args.len() {
-        args.push(String::new());
+impl Spec {
+    fn c_format(&self, length: &str) -> String {
+        let mut ans = format!("%{}", &self.flags);
+        if let Some(w) = self.width {
+            ans += &w.to_string();
+        }
+        if let Some(p) = self.precision {
+            ans += &format!(".{}", p);
+        }
+        ans + length + &self.conv.to_string()
     }
 
-    for i in 0..parts.len() {
-        if parts[i].contains("%d") {
-            if let Ok(_) = args[i].parse::<i32>() {
-                ans += &parts[i].replace("%d", &args[i]);
-            }
-        }else if parts[i].contains("%q") {
-                ans += &parts[i].replace("%q", &("'".to_owned() + &args[i] + "'"));
-        }else {
-            ans += &sprintf::sprintf!(&parts[i], args[i])?;
+    fn pad(&self, mut s: Vec<u8>, use_precision: bool) -> Vec<u8> {
+        if let (true, Some(p)) = (use_precision, self.precision) {
+            s.truncate(p as usize);
+        }
+
+        let width = self.width.unwrap_or(0) as usize;
+        if s.len() >= width {
+            return s;
+        }
+
+        let padding = vec![b' '; width - s.len()];
+        match self.flags.contains('-') {
+            true  => [s, padding].concat(),
+            false => [padding, s].concat(),
         }
     }
+}
 
-    if let Some(s) = tail {
-        ans += &s;
+fn char_code(s: &str) -> Option<i64> {
+    if ! s.starts_with('\'') && ! s.starts_with('"') {
+        return None;
     }
-    Ok(ans)
+    Some(s.chars().nth(1).map(|c| c as i64).unwrap_or(0))
 }
 
-pub fn printf(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() < 2 || args[1] == "--help"
-    || args[1] == "-v" && args.len() == 3 {
-        let msg = format!("printf: usage: printf [-v var] format [arguments]");
-        error::print(&msg, core);
-        return 2;
-    }
-
-    if args[1] == "-v" && args.len() == 2 {
-        let msg = format!("printf: -v: option requires an argument");
-        error::print(&msg, core);
-        let msg = format!("printf: usage: printf [-v var] format [arguments]");
-        error::print(&msg, core);
-        return 2;
-    }
-
-    if args[1] == "-v" {
-        let s = match output(&args[3], &mut args[4..].to_vec()) {
-            Ok(ans) => ans,
-            Err(e) => {
-                let msg = format!("printf: {:?}", e);
-                error::print(&msg, core);
-                return 1;
-            },
+fn to_int(s: &str) -> Result<i64, NumError> {
+    if let Some(n) = char_code(s) {
+        return Ok(n);
+    }
+
+    let trimmed = s.trim_start();
+    if trimmed.is_empty() {
+        return match s.is_empty() {
+            true  => Ok(0),
+            false => Err(NumError::Invalid(0)),
         };
+    }
 
-        if args[2].contains("[") {
-            let tokens = args[2].split('[').collect::<Vec<&str>>();
-            let name = tokens[0].to_string();
-            let subscript = tokens[1].split(']').nth(0).unwrap().to_string();
+    let (minus, body) = match trimmed.chars().next() {
+        Some('-') => (true, &trimmed[1..]),
+        Some('+') => (false, &trimmed[1..]),
+        _         => (false, trimmed),
+    };
+
+    let (radix, digits) = if body.starts_with("0x") || body.starts_with("0X") {
+        (16, &body[2..])
+    }else if body.starts_with('0') && body.len() > 1 {
+        (8, &body[1..])
+    }else{
+        (10, body)
+    };
+
+    let len = digits.chars().take_while(|c| c.is_digit(radix)).count();
+    let mut value: i128 = 0;
+    for c in digits[..len].chars() {
+        value = value * radix as i128 + c.to_digit(radix).unwrap() as i128;
+        if value > i64::MAX as i128 + 1 {
+            break;
+        }
+    }
 
-            let result = match subscript.parse::<usize>() {
-                Ok(n) => core.db.set_array_elem(&name, &s, n, None),
-                _ => core.db.set_assoc_elem(&name, &subscript, &s, None),
+    if minus {
+        value = -value;
+    }
+
+    if value > i64::MAX as i128 {
+        return Err(NumError::OutOfRange(i64::MAX));
+    }
+    if value < i64::MIN as i128 {
+        return Err(NumError::OutOfRange(i64::MIN));
+    }
+    if len < digits.len() || (len == 0 && radix != 8) {
+        return Err(NumError::Invalid(value as i64));
+    }
+    Ok(value as i64)
+}
+
+fn to_float(s: &str) -> Result<f64, f64> {
+    if let Some(n) = char_code(s) {
+        return Ok(n as f64);
+    }
+
+    let trimmed = s.trim_start();
+    if s.is_empty() {
+        return Ok(0.0);
+    }
+
+    let mut ends = trimmed.char_indices().map(|(i, _)| i).collect::<Vec<usize>>();
+    ends.push(trimmed.len());
+
+    for end in ends.into_iter().rev() {
+        if let Ok(n) = trimmed[..end].parse::<f64>() {
+            return match end == trimmed.len() {
+                true  => Ok(n),
+                false => Err(n),
             };
-            if let Err(e) = result {
-                let msg = format!("printf: {:?}", e);
+        }
+    }
+    Err(0.0)
+}
+
+fn c_sprintf(format: &str, value: CValue) -> Vec<u8> {
+    let format = match CString::new(format) {
+        Ok(f) => f,
+        _     => return vec![],
+    };
+
+    let call = |buf: *mut c_char, size: usize| unsafe {
+        match value {
+            CValue::Int(n)   => libc::snprintf(buf, size, format.as_ptr(), n as libc::c_longlong),
+            CValue::Float(f) => libc::snprintf(buf, size, format.as_ptr(), f as libc::c_double),
+        }
+    };
+
+    let len = call(ptr::null_mut(), 0);
+    if len < 0 {
+        return vec![];
+    }
+
+    let mut buf = vec![0u8; len as usize + 1];
+    call(buf.as_mut_ptr() as *mut c_char, buf.len());
+    buf.truncate(len as usize);
+    buf
+}
+
+fn strftime(core: &mut ShellCore, format: &str, time: i64) -> Vec<u8> {
+    let format = match format.is_empty() {
+        true  => CString::new("%X"),
+        false => CString::new(format),
+    };
+    let format = match format {
+        Ok(f) => f,
+        _     => return vec![],
+    };
+
+    /* TZ of the shell is given to the process only while the time is converted */
+    let env_tz = env::var_os("TZ");
+    let tz = core.db.get_param("TZ").unwrap_or_default(); // TZ in the environment is imported here
+    match (core.db.has_value("TZ"), tz.is_empty()) {
+        (false, _)    => env::remove_var("TZ"),
+        (true, true)  => env::set_var("TZ", "UTC0"),
+        (true, false) => env::set_var("TZ", &tz),
+    }
+
+    let t = time as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0u8; 128];
+    unsafe {
+        tzset();
+        libc::localtime_r(&t, &mut tm);
+    }
+
+    match env_tz {
+        Some(tz) => env::set_var("TZ", tz),
+        None     => env::remove_var("TZ"),
+    }
+    unsafe { tzset() };
+
+    loop {
+        let len = unsafe {
+            libc::strftime(buf.as_mut_ptr() as *mut c_char, buf.len(), format.as_ptr(), &tm)
+        };
+        if len > 0 || buf.len() > 8192 {
+            buf.truncate(len);
+            return buf;
+        }
+        buf.resize(buf.len()*2, 0);
+    }
+}
+
+fn push_char(c: char, out: &mut Vec<u8>) {
+    let mut tmp = [0u8; 4];
+    out.extend(c.encode_utf8(&mut tmp).as_bytes());
+}
+
+/* returns the number of consumed chars after the backslash and whether \c appears */
+fn escape(chars: &[char], out: &mut Vec<u8>, in_b: bool, core: &mut ShellCore) -> (usize, bool) {
+    let c = match chars.first() {
+        Some(c) => *c,
+        None    => {
+            out.push(b'\\');
+            return (0, false);
+        },
+    };
+
+    let byte = match c {
+        'a' => 7,
+        'b' => 8,
+        'e' | 'E' => 27,
+        'f' => 12,
+        'n' => b'\n',
+        'r' => b'\r',
+        't' => b'\t',
+        'v' => 11,
+        '\\' => b'\\',
+        '\'' | '"' | '?' if ! in_b => c as u8,
+        'c' if in_b => return (1, true),
+        '0'..='7' => {
+            let max = if c == '0' && in_b { 4 } else { 3 };
+            let len = chars.iter().take(max).take_while(|c| c.is_digit(8)).count();
+            let s = chars[..len].iter().collect::<String>();
+            out.push((u32::from_str_radix(&s, 8).unwrap() & 0xFF) as u8);
+            return (len, false);
+        },
+        'x' | 'u' | 'U' => {
+            let max = match c {
+                'x' => 2,
+                'u' => 4,
+                _   => 8,
+            };
+            let len = chars[1..].iter().take(max).take_while(|c| c.is_ascii_hexdigit()).count();
+            if len == 0 {
+                let msg = match c {
+                    'x' => "printf: missing hex digit for \\x".to_string(),
+                    _   => format!("printf: missing unicode digit for \\{}", c),
+                };
                 error::print(&msg, core);
-                return 2;
+                out.push(b'\\');
+                return (0, false);
             }
-            return 0;
+
+            let s = chars[1..len+1].iter().collect::<String>();
+            let num = u32::from_str_radix(&s, 16).unwrap();
+            match (c, char::from_u32(num)) {
+                ('x', _) => out.push(num as u8),
+                (_, Some(ch)) => push_char(ch, out),
+                _ => {},
+            }
+            return (len+1, false);
+        },
+        _ => {
+            out.push(b'\\');
+            return (0, false);
+        },
+    };
+
+    out.push(byte);
+    (1, false)
+}
+
+fn expand_escapes(s: &str, core: &mut ShellCore) -> (Vec<u8>, bool) {
+    let chars = s.chars().collect::<Vec<char>>();
+    let mut ans = vec![];
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        if chars[pos] != '\\' {
+            push_char(chars[pos], &mut ans);
+            pos += 1;
+            continue;
         }
-        if ! core.db.set_param(&args[2], &s, None).is_ok() {
-            return 2;
+
+        let (len, stop) = escape(&chars[pos+1..], &mut ans, true, core);
+        if stop {
+            return (ans, true);
         }
+        pos += len + 1;
+    }
 
-        return 0;
+    (ans, false)
+}
+
+fn backslash_quote(s: &str) -> String {
+    let mut ans = String::new();
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            ' ' | '\t' | '\n' | '\'' | '"' | '\\' | '|' | '&' | ';' | '(' | ')'
+            | '<' | '>' | '!' | '{' | '}' | '*' | '[' | '?' | ']' | '^'
+            | '$' | '`' | ',' => ans.push('\\'),
+            '~' | '#' if i == 0 => ans.push('\\'),
+            _ => {},
+        }
+        ans.push(c);
     }
+    ans
+}
 
-    let s = match output(&args[1], &mut args[2..].to_vec()) {
-        Ok(ans) => ans,
-        Err(e) => {
-            let msg = format!("printf: {:?}", e);
-            error::print(&msg, core);
-            return 1;
+fn ansi_c_quote(s: &str) -> String {
+    let mut ans = "$'".to_string();
+    for c in s.chars() {
+        match c {
+            '\x07' => ans += "\\a",
+            '\x08' => ans += "\\b",
+            '\x1b' => ans += "\\E",
+            '\x0c' => ans += "\\f",
+            '\n'   => ans += "\\n",
+            '\r'   => ans += "\\r",
+            '\t'   => ans += "\\t",
+            '\x0b' => ans += "\\v",
+            '\\'   => ans += "\\\\",
+            '\''   => ans += "\\'",
+            c if c.is_control() => ans += &format!("\\{:03o}", c as u32 & 0xFF),
+            c => ans.push(c),
+        }
+    }
+    ans + "'"
+}
+
+pub fn quote(s: &str) -> String {
+    if s.is_empty() {
+        return "''".to_string();
+    }
+
+    match s.chars().any(|c| c.is_control()) {
+        true  => ansi_c_quote(s),
+        false => backslash_quote(s),
+    }
+}
+
+fn parse_spec(chars: &[char], args: &mut Arguments, core: &mut ShellCore)
+    -> Result<(Spec, usize), String> {
+    let mut spec = Spec::default();
+    let mut pos = 0;
+    let fail = |pos: usize| chars[..pos].iter().collect::<String>();
+
+    while pos < chars.len() && "#'-+ 0".contains(chars[pos]) {
+        spec.flags.push(chars[pos]);
+        pos += 1;
+    }
+
+    if pos < chars.len() && chars[pos] == '*' {
+        let w = args.next_int(core);
+        if w < 0 {
+            spec.flags.push('-');
+        }
+        spec.width = Some(w.unsigned_abs().min(i32::MAX as u64) as i64);
+        pos += 1;
+    }else{
+        let len = chars[pos..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if len > 0 {
+            spec.width = chars[pos..pos+len].iter().collect::<String>().parse::<i64>().ok();
+            pos += len;
+        }
+    }
+
+    if pos < chars.len() && chars[pos] == '.' {
+        pos += 1;
+        if pos < chars.len() && chars[pos] == '*' {
+            let p = args.next_int(core);
+            spec.precision = if p < 0 { None } else { Some(p) };
+            pos += 1;
+        }else{
+            let len = chars[pos..].iter().take_while(|c| c.is_ascii_digit()).count();
+            let num = chars[pos..pos+len].iter().collect::<String>();
+            spec.precision = Some(num.parse::<i64>().unwrap_or(0));
+            pos += len;
+        }
+    }
+
+    while pos < chars.len() && "hjlLtz".contains(chars[pos]) {
+        pos += 1;
+    }
+
+    if pos < chars.len() && chars[pos] == '(' {
+        let len = match chars[pos..].iter().position(|c| *c == ')') {
+            Some(n) => n,
+            None    => return Err(format!("`{}': missing time format specification", fail(pos+1))),
+        };
+        spec.datefmt = chars[pos+1..pos+len].iter().collect();
+        pos += len + 1;
+        if pos >= chars.len() || chars[pos] != 'T' {
+            return Err(format!("`{}': invalid format character", fail(pos)));
+        }
+    }
+
+    match chars.get(pos) {
+        Some(c) => spec.conv = *c,
+        None    => return Err(format!("`%{}': missing format character", fail(pos))),
+    }
+
+    Ok((spec, pos+1))
+}
+
+fn convert(spec: &Spec, args: &mut Arguments, core: &mut ShellCore) -> Result<(Vec<u8>, bool), String> {
+    let ans = match spec.conv {
+        'd' | 'i' => {
+            let n = args.next_int(core);
+            let spec_d = Spec { conv: 'd', ..spec.clone() };
+            c_sprintf(&spec_d.c_format("ll"), CValue::Int(n))
+        },
+        'o' | 'u' | 'x' | 'X' => {
+            let n = args.next_int(core);
+            c_sprintf(&spec.c_format("ll"), CValue::Int(n))
+        },
+        'e' | 'E' | 'f' | 'F' | 'g' | 'G' | 'a' | 'A' => {
+            let f = args.next_float(core);
+            c_sprintf(&spec.c_format(""), CValue::Float(f))
+        },
+        'c' => {
+            let s = args.next_str();
+            let mut ans = vec![];
+            match s.chars().next() {
+                Some(c) => push_char(c, &mut ans),
+                None    => ans.push(0),
+            }
+            spec.pad(ans, false)
         },
+        's' => spec.pad(args.next_str().into_bytes(), true),
+        'q' => spec.pad(quote(&args.next_str()).into_bytes(), true),
+        'b' => {
+            let (ans, stop) = expand_escapes(&args.next_str(), core);
+            return Ok((spec.pad(ans, true), stop));
+        },
+        'T' => {
+            let time = match args.rest() {
+                true  => args.next_int(core),
+                false => -1,
+            };
+
+            let time = match time {
+                -1 => nix::time::clock_gettime(nix::time::ClockId::CLOCK_REALTIME)
+                      .map(|t| t.tv_sec()).unwrap_or(0),
+                -2 => core.start_time,
+                t  => t,
+            };
+            spec.pad(strftime(core, &spec.datefmt, time), true)
+        },
+        c => return Err(format!("`{}': invalid format character", c)),
+    };
+
+    Ok((ans, false))
+}
+
+/* returns true when the output should be stopped by \c */
+fn format_once(format: &str, args: &mut Arguments, out: &mut Vec<u8>,
+               core: &mut ShellCore) -> Result<bool, String> {
+    let chars = format.chars().collect::<Vec<char>>();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        match chars[pos] {
+            '\\' => {
+                let (len, _) = escape(&chars[pos+1..], out, false, core);
+                pos += len + 1;
+            },
+            '%' if chars.get(pos+1) == Some(&'%') => {
+                out.push(b'%');
+                pos += 2;
+            },
+            '%' => {
+                let (spec, len) = parse_spec(&chars[pos+1..], args, core)?;
+                let (s, stop) = convert(&spec, args, core)?;
+                out.extend(s);
+                if stop {
+                    return Ok(true);
+                }
+                pos += len + 1;
+            },
+            c => {
+                push_char(c, out);
+                pos += 1;
+            },
+        }
+    }
+
+    Ok(false)
+}
+
+fn output(format: &str, args: &mut Arguments, out: &mut Vec<u8>,
+          core: &mut ShellCore) -> Result<(), String> {
+    loop {
+        let prev_pos = args.pos;
+        if format_once(format, args, out, core)? {
+            return Ok(());
+        }
+
+        if ! args.rest() || args.pos == prev_pos {
+            return Ok(());
+        }
+    }
+}
+
+/* the subscript of an indexed array is an arithmetic expression */
+fn array_index(core: &mut ShellCore, name: &str, subscript: &str) -> Result<usize, ExecError> {
+    let mut feeder = Feeder::new(&format!("[{}]", subscript));
+    let index = match Subscript::parse(&mut feeder, core) {
+        Ok(Some(mut s)) if feeder.len() == 0 => s.eval(core, name)?,
+        _ => return Err(ExecError::ArrayIndexInvalid(subscript.to_string())),
     };
-    print!("{}", &s);
-    stdout().flush().unwrap();
-    0
+    index.parse::<usize>().map_err(|_| ExecError::ArrayIndexInvalid(index))
+}
+
+fn set_var(core: &mut ShellCore, name: &str, value: &str) -> i32 {
+    let result = if name.contains('[') && name.ends_with(']') {
+        let pos = name.find('[').unwrap();
+        let subscript = &name[pos+1..name.len()-1];
+        let name = &name[..pos];
+
+        match core.db.is_assoc(name) {
+            true  => core.db.set_assoc_elem(name, &subscript.to_string(), &value.to_string(), None),
+            false => array_index(core, name, subscript)
+                     .and_then(|n| core.db.set_array_elem(name, &value.to_string(), n, None)),
+        }
+    }else{
+        core.db.set_param(name, value, None)
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            let msg: String = From::from(e);
+            error::print(&format!("printf: {}", msg), core);
+            2
+        },
+    }
+}
+
+fn usage() -> i32 {
+    super::help::print_usage("printf");
+    2
+}
+
+pub fn printf(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let mut args = args[1..].to_vec();
+    let mut var = None;
+
+    if args.first().map(|a| a.as_str()) == Some("--help") {
+        return usage();
+    }
+
+    if args.first().map(|a| a.as_str()) == Some("-v") {
+        if args.len() == 1 {
+            error::print("printf: -v: option requires an argument", core);
+            return usage();
+        }
+        args.remove(0);
+        var = Some(args.remove(0));
+    }
+
+    if args.first().map(|a| a.as_str()) == Some("--") {
+        args.remove(0);
+    }else if let Some(a) = args.first() {
+        if a.starts_with('-') && a.len() > 1 && var.is_none() {
+            error::print(&format!("printf: {}: invalid option", &a[..2]), core);
+            return usage();
+        }
+    }
+
+    if args.is_empty() {
+        return usage();
+    }
+
+    let format = args.remove(0);
+    let mut arguments = Arguments { list: args, pos: 0, exit_status: 0 };
+    let mut s = vec![];
+    if let Err(msg) = output(&format, &mut arguments, &mut s, core) {
+        error::print(&format!("printf: {}", msg), core);
+        arguments.exit_status = 1;
+    }
+
+    if let Some(name) = var {
+        let status = set_var(core, &name, &String::from_utf8_lossy(&s));
+        return match status {
+            0 => arguments.exit_status,
+            n => n,
+        };
+    }
+
+    let mut out = stdout();
+    let _ = out.write_all(&s);
+    let _ = out.flush();
+    arguments.exit_status
 }
@@ -2,7 +2,7 @@
 //SPDX-FileCopyrightText: 2023 @caro@mi.shellgei.org
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
+use crate::{error, ShellCore};
 
 pub fn pwd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if args.len() == 1 || &args[1][..1] != "-" { // $ pwd, $ pwd aaa
@@ -13,8 +13,8 @@ pub fn pwd(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
         "-P" => show_pwd(core, true), // シンボリックリンク名を解決して表示
         "-L" => show_pwd(core, false), // シンボリックリンク名をそのまま表示（bash default）
         _ => {
-            eprintln!("sush: pwd: {}: invalid option", &args[1]);
-            eprintln!("pwd: usage: pwd [-LP]");
+            error::print(&format!("pwd: {}: invalid option", &args[1]), core);
+            super::help::print_usage("pwd");
             1
         },
     }
@@ -1,8 +1,52 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
-use crate::error;
+use crate::{error, signal, ShellCore};
+use crate::error::exec::ExecError;
+use crate::error::input::InputError;
+use crate::feeder::terminal;
+use crate::utils::arg;
+use crate::utils::fd_reader::{FdReader, ReadError};
+use nix::{fcntl, libc};
+use nix::poll::{PollFd, PollFlags, PollTimeout};
+use nix::sys::termios;
+use nix::sys::termios::{LocalFlags, SetArg, SpecialCharacterIndices};
+use nix::unistd;
+use std::io::Write;
+use std::os::fd::{BorrowedFd, RawFd};
+use std::time::{Duration, Instant};
+
+struct Options {
+    raw: bool,
+    array: Option<String>,
+    delim: u8,
+    nchars: Option<usize>,
+    exact: bool,
+    prompt: String,
+    silent: bool,
+    timeout: Option<f64>,
+    fd: RawFd,
+    editor: bool,
+    initial: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            raw: false,
+            array: None,
+            delim: b'\n',
+            nchars: None,
+            exact: false,
+            prompt: String::new(),
+            silent: false,
+            timeout: None,
+            fd: 0,
+            editor: false,
+            initial: String::new(),
+        }
+    }
+}
 
 fn is_varname(s :&String) -> bool {
     if s.is_empty() {
@@ -20,54 +64,270 @@ fn is_varname(s :&String) -> bool {
     s.chars().position(|c| !name_c(c)) == None
 }
 
-pub fn read(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() <= 1 {
-        return 0;
+fn parse_options(args: &[String]) -> Result<(Options, Vec<String>), String> {
+    let (opts, names) = arg::getopt(args, "a:d:ei:n:N:p:rst:u:")?;
+    let mut ans = Options::default();
+
+    for (opt, value) in opts {
+        let value = value.unwrap_or_default();
+        match opt {
+            'a' => ans.array = Some(value),
+            'd' => ans.delim = *value.as_bytes().first().unwrap_or(&0),
+            'e' => ans.editor = true,
+            'i' => ans.initial = value,
+            'n' | 'N' => {
+                match value.parse::<usize>() {
+                    Ok(n) => ans.nchars = Some(n),
+                    _ => return Err(format!("{}: invalid number", &value)),
+                }
+                ans.exact = opt == 'N';
+            },
+            'p' => ans.prompt = value,
+            'r' => ans.raw = true,
+            's' => ans.silent = true,
+            't' => match value.parse::<f64>() {
+                Ok(t) if t >= 0.0 => ans.timeout = Some(t),
+                _ => return Err(format!("{}: invalid timeout specification", &value)),
+            },
+            'u' => match value.parse::<RawFd>() {
+                Ok(fd) if fd >= 0 && fcntl::fcntl(fd, fcntl::F_GETFD).is_ok() => ans.fd = fd,
+                _ => return Err(format!("{}: invalid file descriptor: Bad file descriptor", &value)),
+            },
+            _ => {},
+        }
     }
 
-    for a in &args[1..] {
-        if ! is_varname(&a) {
-            eprintln!("bash: read: `{}': not a valid identifier", &a);
-            return 1;
-        }else{
-            if let Err(e) = core.db.set_param(&a, "", None) {
-                let msg = format!("{:?}", &e);
-                error::print(&msg, core);
-                return 1;
-            }
+    for name in names.iter().chain(ans.array.iter()) {
+        if ! is_varname(name) {
+            return Err(format!("`{}': not a valid identifier", name));
         }
     }
 
-    let mut line = String::new();
-    let len = std::io::stdin()
-        .read_line(&mut line)
-        .expect("SUSHI INTERNAL ERROR: Failed to read line");
+    Ok((ans, names))
+}
 
-    let mut pos = 1;
-    let mut overflow = String::new();
-    for w in line.trim_end().split(' ') {
-        if pos < args.len()-1 {
-            if let Err(e) = core.db.set_param(&args[pos], &w, None) {
-                let msg = format!("{:?}", &e);
-                error::print(&msg, core);
-                return 1;
+fn get_ifs(core: &mut ShellCore) -> String {
+    match core.db.has_value("IFS") {
+        true  => core.db.get_param("IFS").unwrap_or_default(),
+        false => " \t\n".to_string(),
+    }
+}
+
+fn is_ifs(c: &(char, bool), ifs: &str) -> bool {
+    ! c.1 && ifs.contains(c.0)
+}
+
+fn is_ifs_blank(c: &(char, bool), ifs: &str) -> bool {
+    is_ifs(c, ifs) && " \t\n".contains(c.0)
+}
+
+fn skip_blank(chars: &[(char, bool)], pos: &mut usize, ifs: &str) {
+    while *pos < chars.len() && is_ifs_blank(&chars[*pos], ifs) {
+        *pos += 1;
+    }
+}
+
+fn get_word(chars: &[(char, bool)], pos: &mut usize, ifs: &str) -> String {
+    let start = *pos;
+    while *pos < chars.len() && ! is_ifs(&chars[*pos], ifs) {
+        *pos += 1;
+    }
+    let ans = chars[start..*pos].iter().map(|c| c.0).collect();
+
+    skip_blank(chars, pos, ifs);
+    if *pos < chars.len() && is_ifs(&chars[*pos], ifs) {
+        *pos += 1;
+        skip_blank(chars, pos, ifs);
+    }
+    ans
+}
+
+fn get_rest(chars: &[(char, bool)], pos: usize, ifs: &str) -> String {
+    let mut tmp = pos;
+    let word = get_word(chars, &mut tmp, ifs);
+    if tmp >= chars.len() {
+        return word;
+    }
+
+    let mut end = chars.len();
+    while end > pos && is_ifs_blank(&chars[end-1], ifs) {
+        end -= 1;
+    }
+    chars[pos..end].iter().map(|c| c.0).collect()
+}
+
+fn assign(core: &mut ShellCore, names: &[String], opts: &Options,
+          chars: &[(char, bool)]) -> Result<(), ExecError> {
+    let ifs = get_ifs(core);
+    let mut pos = 0;
+    skip_blank(chars, &mut pos, &ifs);
+
+    if let Some(array) = &opts.array {
+        let mut words = vec![];
+        while pos < chars.len() {
+            words.push(get_word(chars, &mut pos, &ifs));
+        }
+        return core.db.set_array(array, words, None);
+    }
+
+    if names.is_empty() {
+        let line: String = chars.iter().map(|c| c.0).collect();
+        return core.db.set_param("REPLY", &line, None);
+    }
+
+    for name in &names[..names.len()-1] {
+        let word = get_word(chars, &mut pos, &ifs);
+        core.db.set_param(name, &word, None)?;
+    }
+
+    let rest = get_rest(chars, pos, &ifs);
+    core.db.set_param(&names[names.len()-1], &rest, None)
+}
+
+/* returns true when the input is terminated by the delimiter or -n/-N */
+fn read_chars(reader: &mut FdReader, opts: &Options,
+              chars: &mut Vec<(char, bool)>) -> Result<bool, ReadError> {
+    let mut escaped = false;
+    loop {
+        if let Some(n) = opts.nchars {
+            if chars.len() >= n {
+                return Ok(true);
             }
-            pos += 1;
-        }else{
-            if overflow.len() != 0 {
-                overflow += " ";
+        }
+
+        let unit = match reader.char()? {
+            Some(u) => u,
+            None    => return Ok(false),
+        };
+
+        if escaped {
+            escaped = false;
+            if unit != [b'\n'] {
+                String::from_utf8_lossy(&unit).chars().for_each(|c| chars.push((c, true)));
             }
-            overflow += &w;
-            if let Err(e) = core.db.set_param(&args[pos], &overflow, None) {
-                let msg = format!("{:?}", &e);
-                error::print(&msg, core);
+            continue;
+        }
+
+        if ! opts.raw && unit == [b'\\'] {
+            escaped = true;
+        }else if ! opts.exact && unit == [opts.delim] {
+            return Ok(true);
+        }else if unit != [0] {
+            String::from_utf8_lossy(&unit).chars().for_each(|c| chars.push((c, false)));
+        }
+    }
+}
+
+fn input_exists(fd: RawFd) -> bool {
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    matches!(nix::poll::poll(&mut fds, PollTimeout::ZERO), Ok(n) if n > 0)
+}
+
+fn set_terminal(opts: &Options) -> Option<termios::Termios> {
+    if ! opts.silent && opts.nchars.is_none() {
+        return None;
+    }
+
+    let fd = unsafe { BorrowedFd::borrow_raw(opts.fd) };
+    let backup = termios::tcgetattr(fd).ok()?;
+    let mut attr = backup.clone();
+    if opts.silent {
+        attr.local_flags.remove(LocalFlags::ECHO);
+    }
+    if opts.nchars.is_some() {
+        attr.local_flags.remove(LocalFlags::ICANON);
+        attr.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+        attr.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
+    }
+    termios::tcsetattr(fd, SetArg::TCSADRAIN, &attr).ok()?;
+    Some(backup)
+}
+
+fn restore_terminal(fd: RawFd, backup: Option<termios::Termios>) {
+    if let Some(attr) = backup {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+        let _ = termios::tcsetattr(fd, SetArg::TCSADRAIN, &attr);
+    }
+}
+
+fn make_reader(core: &mut ShellCore, opts: &mut Options) -> Result<FdReader, i32> {
+    let tty = unistd::isatty(opts.fd) == Ok(true);
+
+    if opts.editor && tty && opts.fd == 0 {
+        opts.delim = b'\n';
+        return match terminal::read_line_with_initial(core, &opts.prompt, &opts.initial) {
+            Ok(line) => Ok(FdReader::from_bytes(line.into_bytes())),
+            Err(InputError::Interrupt) => Err(130),
+            Err(_) => Err(1),
+        };
+    }
+
+    if tty && ! opts.prompt.is_empty() {
+        eprint!("{}", &opts.prompt);
+        let _ = std::io::stderr().flush();
+    }
+
+    let mut reader = FdReader::new(opts.fd);
+    if let Some(t) = opts.timeout {
+        reader.deadline = Some(Instant::now() + Duration::from_secs_f64(t));
+    }
+    reader.interruptible = true;
+    Ok(reader)
+}
+
+pub fn read(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut opts, names) = match parse_options(&args[1..]) {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("read: {}", msg), core);
+            if ! msg.ends_with("invalid option") && ! msg.ends_with("requires an argument") {
                 return 1;
             }
-        }
+            super::help::print_usage("read");
+            return 2;
+        },
+    };
+
+    if opts.timeout == Some(0.0) {
+        return match input_exists(opts.fd) {
+            true  => 0,
+            false => 1,
+        };
     }
 
-    match len == 0 {
-        true  => 1,
-        false => 0,
+    let mut reader = match make_reader(core, &mut opts) {
+        Ok(r) => r,
+        Err(status) => return status,
+    };
+
+    let tty_backup = set_terminal(&opts);
+    let mut chars = vec![];
+    let result = loop { // traps run during read, but SIGINT stops it
+        match read_chars(&mut reader, &opts, &mut chars) {
+            Err(ReadError::Signal(sig)) if sig != libc::SIGINT => signal::check_trap(core),
+            r => break r,
+        }
+    };
+    restore_terminal(opts.fd, tty_backup);
+    drop(reader);
+
+    let exit_status = match result {
+        Ok(true)  => 0,
+        Ok(false) => 1,
+        Err(ReadError::Timeout) => 142,
+        Err(ReadError::Signal(sig)) => 128 + sig,
+        Err(ReadError::Errno(e)) => {
+            let msg = format!("read: read error: {}: {}", opts.fd, e.desc());
+            error::print(&msg, core);
+            return 1;
+        },
+    };
+
+    if let Err(e) = assign(core, &names, &opts, &chars) {
+        e.print(core);
+        return 1;
     }
+
+    exit_status
 }
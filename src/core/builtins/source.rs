@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{file_check, Script, ShellCore, Feeder};
+use crate::{file_check, signal, Script, ShellCore, Feeder};
 use crate::elements::io;
 use std::fs::File;
 use std::os::fd::IntoRawFd;
@@ -9,7 +9,7 @@ use std::os::fd::IntoRawFd;
 pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     if args.len() < 2 {
         eprintln!("sush: source: filename argument required");
-        eprintln!("source: usage: source filename [arguments]");
+        super::help::print_usage(&args[0]);
         return 2;
     }
 
@@ -33,6 +33,7 @@ pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     core.read_stdin = true;
     core.source_function_level += 1;
     core.source_level += 1;
+    core.push_call_stack("source", &args[1], &args[2..]);
 
     let mut feeder = Feeder::new("");
     loop {
@@ -53,6 +54,9 @@ pub fn source(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
     }
 
     io::replace(backup, 0);
+    core.return_flag = false;
+    signal::run_pseudo_trap(core, signal::RETURN);
+    core.pop_call_stack();
     core.source_function_level -= 1;
     core.source_level -= 1;
     core.return_flag = false;
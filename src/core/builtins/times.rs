@@ -0,0 +1,30 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::core::MeasuredTime;
+use nix::sys::resource::{self, UsageWho};
+use nix::sys::time::{TimeVal, TimeValLike};
+
+fn usage(who: UsageWho) -> MeasuredTime {
+    match resource::getrusage(who) {
+        Ok(u) => MeasuredTime {
+            user: u.user_time(),
+            sys: u.system_time(),
+            ..Default::default()
+        },
+        Err(_) => MeasuredTime::default(),
+    }
+}
+
+fn format(t: &TimeVal) -> String {
+    let ms = t.num_milliseconds();
+    format!("{}m{}.{:03}s", ms/60000, ms%60000/1000, ms%1000)
+}
+
+pub fn times(_: &mut ShellCore, _: &mut Vec<String>) -> i32 {
+    for t in [usage(UsageWho::RUSAGE_SELF), usage(UsageWho::RUSAGE_CHILDREN)] {
+        println!("{} {}", format(&t.user), format(&t.sys));
+    }
+    0
+}
@@ -2,109 +2,96 @@
 //SPDX-FileCopyrightText: 2023 @caro@mi.shellgei.org
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
-use crate::signal;
-use crate::error::exec::ExecError;
-use nix::sys::signal::Signal;
-use std::str::FromStr;
-use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
-use std::sync::atomic::Ordering::Relaxed;
-use std::{thread, time};
-use signal_hook::iterator::Signals;
-
-pub fn trap(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
-    if args.len() < 3 { // TODO: print the list of trap entries if args.len() == 1
-        eprintln!("trap: usage: trap arg signal_spec ...");
-        return 2;
-    }
-
-    let forbiddens = Vec::from(signal_hook::consts::FORBIDDEN);
-    let signals = match args_to_nums(&args[2..], &forbiddens){
-        Ok(v) => v,
-        Err(e) => {
-            e.print(core);
-            return 1;
-        }
-    };
-
-    let mut exit = false;
-    let mut valid_signals = vec![];
-    for n in &signals {
-        if *n == 0 {
-            exit = true;
-            continue;
-        }
-
-        if let Ok(s) = TryFrom::try_from(*n) {
-            signal::ignore(s);
-            valid_signals.push(*n);
-            continue;
-        };
-
-        ExecError::Other(format!("trap: {}: invalid signal specification", n)).print(core);
-        return 1;
-    }
-
-    if ! valid_signals.is_empty() {
-        run_thread(valid_signals, &args[1], core);
+use crate::{error, signal, ShellCore};
+use crate::utils::arg;
+
+fn to_num(arg: &str) -> Option<i32> {
+    match arg.to_uppercase().as_str() {
+        "EXIT" | "SIGEXIT" | "0" => Some(0),
+        "DEBUG" => Some(signal::DEBUG),
+        "ERR" => Some(signal::ERR),
+        "RETURN" => Some(signal::RETURN),
+        _ => signal::name_to_num(arg),
     }
+}
 
-    if exit {
-        core.exit_script = args[1].clone();
+fn to_name(num: i32) -> String {
+    match num {
+        0 => "EXIT".to_string(),
+        signal::DEBUG => "DEBUG".to_string(),
+        signal::ERR => "ERR".to_string(),
+        signal::RETURN => "RETURN".to_string(),
+        n => format!("SIG{}", signal::num_to_name(n).unwrap_or(n.to_string())),
     }
-
-    0
 }
 
-fn run_thread(signal_nums: Vec<i32>, script: &String, core: &mut ShellCore) {
-    core.trapped.push((Arc::new(AtomicBool::new(false)), script.clone()));
-
-    let trap = Arc::clone(&core.trapped.last().unwrap().0);
-
-    thread::spawn(move || {
-        let mut signals = Signals::new(signal_nums.clone())
-                          .expect("sush(fatal): cannot prepare signal data");
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
 
-        loop {
-            thread::sleep(time::Duration::from_millis(100));
-            for signal in signals.pending() {
-                if signal_nums.contains(&signal) {
-                    trap.store(true, Relaxed);
-                }
-            }
+fn print(core: &mut ShellCore, nums: &[i32]) {
+    let mut traps = core.parent_traps.clone();
+    traps.extend(core.traps.clone());
+    for (num, command) in &traps {
+        if nums.is_empty() || nums.contains(num) {
+            println!("trap -- {} {}", quote(command), to_name(*num));
         }
-    });
+    }
 }
 
-fn arg_to_num(arg: &str, forbiddens: &Vec<i32>) -> Result<i32, ExecError> {
-    if arg == "EXIT" || arg == "0" {
-        return Ok(0);
+fn to_nums(core: &mut ShellCore, args: &[String]) -> (Vec<i32>, i32) {
+    let mut nums = vec![];
+    let mut exit_status = 0;
+    for a in args {
+        match to_num(a) {
+            Some(n) => nums.push(n),
+            None => {
+                error::print(&format!("trap: {}: invalid signal specification", a), core);
+                exit_status = 1;
+            },
+        }
     }
+    (nums, exit_status)
+}
 
-    if let Ok(n) = Signal::from_str(arg) {
-        return Ok(n as i32);
-    }
+pub fn trap(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, operands) = match arg::getopt(&args[1..], "lp") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("trap: {}", msg), core);
+            super::help::print_usage("trap");
+            return 2;
+        },
+    };
 
-    if let Ok(n) = Signal::from_str(&("SIG".to_owned() + arg)) {
-        return Ok(n as i32);
+    if opts.iter().any(|o| o.0 == 'l') {
+        signal::print_signal_list();
+        return 0;
     }
 
-    if let Ok(n) = arg.parse::<i32>() {
-        if forbiddens.contains(&n) {
-            return Err(ExecError::Other(format!("trap: {}: forbidden signal for trap", arg)));
+    if operands.is_empty() || opts.iter().any(|o| o.0 == 'p') {
+        let (nums, exit_status) = to_nums(core, &operands);
+        if exit_status == 0 || ! nums.is_empty() {
+            print(core, &nums);
         }
-        return Ok(n);
+        return exit_status;
     }
 
-    return Err(ExecError::Other(format!("trap: {}: invalid signal specification", arg)));
-}
+    /* a single operand or a leading signal number means resetting */
+    let reset = operands.len() == 1 || operands[0] == "-"
+                || (! operands[0].is_empty() && operands[0].chars().all(|c| c.is_ascii_digit()));
+    let signals = match reset && operands[0] != "-" {
+        true  => &operands[..],
+        false => &operands[1..],
+    };
 
-fn args_to_nums(args: &[String], forbiddens: &Vec<i32>) -> Result<Vec<i32>, ExecError> {
-    let mut ans = vec![];
-    for a in args {
-        let n = arg_to_num(a, forbiddens)?;
-        ans.push(n);
+    let (nums, exit_status) = to_nums(core, signals);
+    core.parent_traps.clear();
+    for n in nums {
+        match reset {
+            true  => signal::reset_trap(core, n),
+            false => signal::set_trap(core, n, &operands[0]),
+        }
     }
-    Ok(ans)
+    exit_status
 }
@@ -0,0 +1,136 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use crate::utils::{arg, file, file_check};
+
+const KEYWORDS: [&str; 22] = ["if", "then", "else", "elif", "fi", "case", "esac",
+    "for", "select", "while", "until", "do", "done", "in", "function", "time",
+    "{", "}", "!", "[[", "]]", "coproc"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Alias(String),
+    Keyword,
+    Function(String),
+    Builtin,
+    File(String, bool), // (path, hashed)
+}
+
+impl Kind {
+    pub fn type_name(&self) -> &str {
+        match self {
+            Kind::Alias(_)    => "alias",
+            Kind::Keyword     => "keyword",
+            Kind::Function(_) => "function",
+            Kind::Builtin     => "builtin",
+            Kind::File(_, _)  => "file",
+        }
+    }
+
+    pub fn describe(&self, name: &str) -> String {
+        match self {
+            Kind::Alias(s)    => format!("{} is aliased to `{}'", name, s),
+            Kind::Keyword     => format!("{} is a shell keyword", name),
+            Kind::Function(s) => format!("{} is a function\n{}", name, s),
+            Kind::Builtin     => format!("{} is a shell builtin", name),
+            Kind::File(p, true)  => format!("{} is hashed ({})", name, p),
+            Kind::File(p, false) => format!("{} is {}", name, p),
+        }
+    }
+}
+
+fn files(core: &mut ShellCore, name: &str, all: bool) -> Vec<Kind> {
+    if name.contains('/') {
+        return match file_check::is_executable(name) && ! file_check::is_dir(name) {
+            true  => vec![Kind::File(name.to_string(), false)],
+            false => vec![],
+        };
+    }
+
+    if ! all {
+        if let Some(p) = core.hash_get(name) {
+            return vec![Kind::File(p, true)];
+        }
+    }
+
+    let paths = core.db.get_param("PATH").unwrap_or_default();
+    let mut ans: Vec<Kind> = file::search_command_all(name, &paths).into_iter()
+                                  .map(|p| Kind::File(p, false)).collect();
+    if ! all {
+        ans.truncate(1);
+    }
+    ans
+}
+
+/* lists what the name means in the order of precedence */
+pub fn kinds(core: &mut ShellCore, name: &str, all: bool, function: bool) -> Vec<Kind> {
+    let mut ans = vec![];
+
+    if let Some(s) = core.aliases.get(name) {
+        ans.push(Kind::Alias(s.clone()));
+    }
+    if KEYWORDS.contains(&name) {
+        ans.push(Kind::Keyword);
+    }
+    if function {
+        if let Some(f) = core.db.functions.get(name) {
+            ans.push(Kind::Function(f.text.trim_end().to_string()));
+        }
+    }
+    if core.builtins.contains_key(name) {
+        ans.push(Kind::Builtin);
+    }
+
+    if all || ans.is_empty() {
+        ans.extend(files(core, name, all));
+    }
+
+    if ! all {
+        ans.truncate(1);
+    }
+    ans
+}
+
+fn usage(core: &mut ShellCore, msg: &str) -> i32 {
+    error::print(&format!("type: {}", msg), core);
+    super::help::print_usage("type");
+    2
+}
+
+pub fn type_(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, names) = match arg::getopt(&args[1..], "afptP") {
+        Ok(ans) => ans,
+        Err(msg) => return usage(core, &msg),
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    let mut exit_status = 0;
+    for name in &names {
+        let found = match has('P') {
+            true  => files(core, name, has('a')),
+            false => kinds(core, name, has('a'), ! has('f')),
+        };
+
+        if found.is_empty() {
+            if ! has('t') && ! has('p') && ! has('P') {
+                error::print(&format!("type: {}: not found", name), core);
+            }
+            exit_status = 1;
+            continue;
+        }
+
+        for kind in found {
+            if has('t') {
+                println!("{}", kind.type_name());
+            }else if has('p') || has('P') {
+                if let Kind::File(path, _) = kind {
+                    println!("{}", path);
+                }
+            }else{
+                println!("{}", kind.describe(name));
+            }
+        }
+    }
+    exit_status
+}
@@ -0,0 +1,176 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use nix::sys::resource::{getrlimit, setrlimit, Resource, rlim_t, RLIM_INFINITY};
+
+struct Limit {
+    opt: char,
+    desc: &'static str,
+    unit: Option<&'static str>,
+    factor: rlim_t,
+    resource: Option<Resource>, // None: pipe size, which is read-only
+}
+
+const fn limit(opt: char, desc: &'static str, unit: Option<&'static str>,
+               factor: rlim_t, resource: Option<Resource>) -> Limit {
+    Limit { opt, desc, unit, factor, resource }
+}
+
+/* in the order of ulimit -a */
+const LIMITS: &[Limit] = &[
+    #[cfg(target_os = "linux")]
+    limit('R', "real-time non-blocking time", Some("microseconds"), 1, Some(Resource::RLIMIT_RTTIME)),
+    limit('c', "core file size", Some("blocks"), 512, Some(Resource::RLIMIT_CORE)),
+    limit('d', "data seg size", Some("kbytes"), 1024, Some(Resource::RLIMIT_DATA)),
+    #[cfg(target_os = "linux")]
+    limit('e', "scheduling priority", None, 1, Some(Resource::RLIMIT_NICE)),
+    limit('f', "file size", Some("blocks"), 512, Some(Resource::RLIMIT_FSIZE)),
+    #[cfg(target_os = "linux")]
+    limit('i', "pending signals", None, 1, Some(Resource::RLIMIT_SIGPENDING)),
+    #[cfg(target_os = "linux")]
+    limit('l', "max locked memory", Some("kbytes"), 1024, Some(Resource::RLIMIT_MEMLOCK)),
+    limit('m', "max memory size", Some("kbytes"), 1024, Some(Resource::RLIMIT_RSS)),
+    limit('n', "open files", None, 1, Some(Resource::RLIMIT_NOFILE)),
+    limit('p', "pipe size", Some("512 bytes"), 512, None),
+    #[cfg(target_os = "linux")]
+    limit('q', "POSIX message queues", Some("bytes"), 1, Some(Resource::RLIMIT_MSGQUEUE)),
+    #[cfg(target_os = "linux")]
+    limit('r', "real-time priority", None, 1, Some(Resource::RLIMIT_RTPRIO)),
+    limit('s', "stack size", Some("kbytes"), 1024, Some(Resource::RLIMIT_STACK)),
+    limit('t', "cpu time", Some("seconds"), 1, Some(Resource::RLIMIT_CPU)),
+    limit('u', "max user processes", None, 1, Some(Resource::RLIMIT_NPROC)),
+    limit('v', "virtual memory", Some("kbytes"), 1024, Some(Resource::RLIMIT_AS)),
+    #[cfg(target_os = "linux")]
+    limit('x', "file locks", None, 1, Some(Resource::RLIMIT_LOCKS)),
+];
+
+const PIPE_SIZE: rlim_t = 4096;
+
+fn usage(core: &mut ShellCore, opt: &str) -> i32 {
+    error::print(&format!("ulimit: {}: invalid option", opt), core);
+    super::help::print_usage("ulimit");
+    2
+}
+
+fn get(limit: &Limit, hard: bool) -> Result<rlim_t, String> {
+    let resource = match limit.resource {
+        Some(r) => r,
+        None    => return Ok(PIPE_SIZE),
+    };
+
+    match getrlimit(resource) {
+        Ok((soft, h)) => Ok(if hard { h } else { soft }),
+        Err(e) => Err(format!("{}: cannot get limit: {}", limit.desc, e.desc())),
+    }
+}
+
+fn to_string(limit: &Limit, value: rlim_t) -> String {
+    match value {
+        RLIM_INFINITY => "unlimited".to_string(),
+        v => (v / limit.factor).to_string(),
+    }
+}
+
+fn print(limit: &Limit, hard: bool, with_desc: bool) -> Result<(), String> {
+    let value = to_string(limit, get(limit, hard)?);
+    if ! with_desc {
+        println!("{}", value);
+        return Ok(());
+    }
+
+    let unit = match limit.unit {
+        Some(u) => format!("({}, -{}) ", u, limit.opt),
+        None    => format!("(-{}) ", limit.opt),
+    };
+    println!("{:<20} {:>20}{}", limit.desc, unit, value);
+    Ok(())
+}
+
+fn set(limit: &Limit, value: &str, hard: bool, soft: bool) -> Result<(), String> {
+    let resource = match limit.resource {
+        Some(r) => r,
+        None    => return Err(format!("{}: cannot modify limit: Invalid argument", limit.desc)),
+    };
+
+    let (cur_soft, cur_hard) = getrlimit(resource)
+        .map_err(|e| format!("{}: cannot get limit: {}", limit.desc, e.desc()))?;
+
+    let new = match value {
+        "unlimited" => RLIM_INFINITY,
+        "hard" => cur_hard,
+        "soft" => cur_soft,
+        v => match v.parse::<rlim_t>().ok().and_then(|n| n.checked_mul(limit.factor)) {
+            Some(n) => n,
+            None    => return Err(format!("{}: invalid number", v)),
+        },
+    };
+
+    let new_soft = if soft { new } else { cur_soft };
+    let new_hard = if hard { new } else { cur_hard };
+    setrlimit(resource, new_soft, new_hard)
+        .map_err(|e| format!("{}: cannot modify limit: {}", limit.desc, e.desc()))
+}
+
+pub fn ulimit(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (mut hard, mut soft, mut all) = (false, false, false);
+    let mut targets: Vec<(char, Option<String>)> = vec![];
+
+    let mut pos = 1;
+    while pos < args.len() {
+        let arg = &args[pos];
+        pos += 1;
+
+        if ! arg.starts_with('-') || arg == "-" {
+            targets.push(('f', Some(arg.clone())));
+            continue;
+        }
+
+        for c in arg[1..].chars() {
+            match c {
+                'H' => hard = true,
+                'S' => soft = true,
+                'a' => all = true,
+                c if LIMITS.iter().any(|l| l.opt == c) => targets.push((c, None)),
+                _ => return usage(core, &format!("-{}", c)),
+            }
+        }
+
+        if pos < args.len() && ! args[pos].starts_with('-') {
+            if let Some(t) = targets.last_mut() {
+                t.1 = Some(args[pos].clone());
+                pos += 1;
+            }
+        }
+    }
+
+    if all {
+        for limit in LIMITS {
+            if let Err(msg) = print(limit, hard, true) {
+                error::print(&format!("ulimit: {}", msg), core);
+                return 1;
+            }
+        }
+        return 0;
+    }
+
+    if targets.is_empty() {
+        targets.push(('f', None));
+    }
+
+    let with_desc = targets.len() > 1;
+    let mut exit_status = 0;
+    for (opt, value) in targets {
+        let limit = LIMITS.iter().find(|l| l.opt == opt).unwrap();
+        let res = match value {
+            None    => print(limit, hard, with_desc),
+            Some(v) => set(limit, &v, hard || ! soft, soft || ! hard),
+        };
+
+        if let Err(msg) = res {
+            error::print(&format!("ulimit: {}", msg), core);
+            exit_status = 1;
+        }
+    }
+    exit_status
+}
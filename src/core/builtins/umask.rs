@@ -0,0 +1,126 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+use crate::utils::arg;
+use nix::sys::stat::{self, Mode};
+
+fn get_mask() -> u32 {
+    let mask = stat::umask(Mode::empty());
+    stat::umask(mask);
+    mask.bits() as u32
+}
+
+fn set_mask(mask: u32) {
+    stat::umask(Mode::from_bits_truncate((mask & 0o777) as _));
+}
+
+fn to_symbolic(mask: u32) -> String {
+    let perm = ! mask & 0o777;
+    let rwx = |shift: u32| -> String {
+        [(4, 'r'), (2, 'w'), (1, 'x')].iter()
+            .filter(|(b, _)| perm >> shift & b != 0)
+            .map(|(_, c)| *c).collect()
+    };
+    format!("u={},g={},o={}", rwx(6), rwx(3), rwx(0))
+}
+
+/* applies clauses like "u=rwx,g+w,o-x" to the permission bits */
+fn parse_symbolic(mode: &str, mask: u32) -> Result<u32, String> {
+    let mut perm = ! mask & 0o777;
+
+    for clause in mode.split(',') {
+        let mut chars = clause.chars().peekable();
+        let mut who = 0;
+        while let Some(c) = chars.next_if(|c| "ugoa".contains(*c)) {
+            who |= match c {
+                'u' => 0o700,
+                'g' => 0o070,
+                'o' => 0o007,
+                _   => 0o777,
+            };
+        }
+        if who == 0 {
+            who = 0o777;
+        }
+
+        loop {
+            let op = match chars.next() {
+                Some(c) if "=+-".contains(c) => c,
+                Some(c) => return Err(format!("`{}': invalid symbolic mode operator", c)),
+                None => return Err(format!("`{}': invalid symbolic mode operator", clause)),
+            };
+
+            let mut bits = 0;
+            while let Some(c) = chars.next_if(|c| ! "=+-".contains(*c)) {
+                bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    _   => return Err(format!("`{}': invalid symbolic mode character", c)),
+                };
+            }
+            bits &= who;
+
+            match op {
+                '=' => perm = (perm & ! who) | bits,
+                '+' => perm |= bits,
+                _   => perm &= ! bits,
+            }
+
+            if chars.peek().is_none() {
+                break;
+            }
+        }
+    }
+
+    Ok(! perm & 0o777)
+}
+
+fn parse_mode(mode: &str, mask: u32) -> Result<u32, String> {
+    if ! mode.starts_with(|c: char| c.is_ascii_digit()) {
+        return parse_symbolic(mode, mask);
+    }
+
+    match u32::from_str_radix(mode, 8) {
+        Ok(n) => Ok(n & 0o777),
+        Err(_) => Err(format!("{}: octal number out of range", mode)),
+    }
+}
+
+pub fn umask(core: &mut ShellCore, args: &mut Vec<String>) -> i32 {
+    let (opts, modes) = match arg::getopt(&args[1..], "pS") {
+        Ok(ans) => ans,
+        Err(msg) => {
+            error::print(&format!("umask: {}", msg), core);
+            super::help::print_usage("umask");
+            return 2;
+        },
+    };
+    let has = |c| opts.iter().any(|o| o.0 == c);
+
+    if let Some(mode) = modes.first() {
+        match parse_mode(mode, get_mask()) {
+            Ok(mask) => set_mask(mask),
+            Err(msg) => {
+                error::print(&format!("umask: {}", msg), core);
+                return 1;
+            },
+        }
+        if ! has('S') {
+            return 0;
+        }
+    }
+
+    let mask = get_mask();
+    let prefix = match (has('p'), has('S')) {
+        (true, true)  => "umask -S ",
+        (true, false) => "umask ",
+        _ => "",
+    };
+    match has('S') {
+        true  => println!("{}{}", prefix, to_symbolic(mask)),
+        false => println!("{}{:04o}", prefix, mask),
+    }
+    0
+}
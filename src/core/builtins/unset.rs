@@ -1,14 +1,28 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::ShellCore;
+use crate::{error, ShellCore};
+
+fn readonly_check(core: &mut ShellCore, name: &str) -> bool {
+    if ! core.db.is_readonly(name) {
+        return false;
+    }
+    error::print(&format!("unset: {}: cannot unset: readonly variable", name), core);
+    true
+}
 
 fn unset_all(core: &mut ShellCore, name: &str) -> i32 {
+    if readonly_check(core, name) {
+        return 1;
+    }
     core.db.unset(name);
     0
 }
 
 fn unset_var(core: &mut ShellCore, name: &str) -> i32 {
+    if readonly_check(core, name) {
+        return 1;
+    }
     core.db.unset_var(name);
     0
 }
@@ -0,0 +1,96 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{error, ShellCore};
+
+const STACK_ARRAYS: [&str; 3] = ["FUNCNAME", "BASH_SOURCE", "BASH_LINENO"];
+
+impl ShellCore {
+    /* called when a script file is run; the bottom of BASH_SOURCE and BASH_LINENO */
+    pub fn init_call_stack(&mut self, script: &str) {
+        let _ = self.db.set_array("BASH_SOURCE", vec![script.to_string()], Some(0));
+        let _ = self.db.set_array("BASH_LINENO", vec!["0".to_string()], Some(0));
+    }
+
+    pub fn current_source(&mut self) -> String {
+        self.db.get_array_all("BASH_SOURCE").first().cloned().unwrap_or_default()
+    }
+
+    /* The frame of a function or a sourced file is inserted at index 0.
+     * BASH_LINENO[0] is the line where the function or file is called. */
+    pub fn push_call_stack(&mut self, funcname: &str, source: &str, args: &[String]) {
+        let lineno = self.db.get_param("LINENO").unwrap_or_default();
+        let mut arrays: Vec<Vec<String>> = STACK_ARRAYS.iter()
+                                   .map(|a| self.db.get_array_all(a)).collect();
+
+        if arrays[0].is_empty() && ! arrays[1].is_empty() {
+            arrays[0].push("main".to_string());
+        }
+
+        for (array, value) in arrays.iter_mut().zip([funcname, source, &lineno]) {
+            array.insert(0, value.to_string());
+        }
+        for (name, array) in STACK_ARRAYS.iter().zip(arrays) {
+            let _ = self.db.set_array(name, array, Some(0));
+        }
+
+        self.arg_frames.insert(0, args.to_vec());
+        self.update_bash_argv();
+    }
+
+    /* LINENO goes back to the line of the call */
+    pub fn pop_call_stack(&mut self) {
+        for name in STACK_ARRAYS {
+            let mut array = self.db.get_array_all(name);
+            if ! array.is_empty() {
+                let value = array.remove(0);
+                if name == "BASH_LINENO" {
+                    let _ = self.db.set_param("LINENO", &value, None);
+                }
+            }
+            if name == "FUNCNAME" && array.len() == 1 && array[0] == "main" {
+                array.clear();
+            }
+            let _ = self.db.set_array(name, array, Some(0));
+        }
+
+        if ! self.arg_frames.is_empty() {
+            self.arg_frames.remove(0);
+        }
+        self.update_bash_argv();
+    }
+
+    /* The frames are always kept, and BASH_ARGV and BASH_ARGC show them only
+     * while extdebug is enabled. The arguments of each frame are stored in reverse
+     * order in BASH_ARGV, and their number in BASH_ARGC. The bottom frame is the
+     * positional parameters of the shell. */
+    pub fn update_bash_argv(&mut self) {
+        if ! self.options.query("extdebug") {
+            return;
+        }
+
+        let mut frames = self.arg_frames.clone();
+        let bottom = self.db.position_parameters.first()
+                     .and_then(|p| p.get(1..)).unwrap_or_default();
+        frames.push(bottom.to_vec());
+
+        let argv = frames.iter().flat_map(|f| f.iter().rev().cloned()).collect();
+        let argc = frames.iter().map(|f| f.len().to_string()).collect();
+        let _ = self.db.set_array("BASH_ARGV", argv, Some(0));
+        let _ = self.db.set_array("BASH_ARGC", argc, Some(0));
+    }
+
+    /* returns false when the number of nested function calls reaches FUNCNEST */
+    pub fn check_funcnest(&mut self, name: &str) -> bool {
+        let max = self.db.get_param("FUNCNEST").unwrap_or_default()
+                      .parse::<i32>().unwrap_or(0);
+        if max <= 0 || self.function_level < max {
+            return true;
+        }
+
+        let msg = format!("{}: maximum function nesting level exceeded ({})", name, max);
+        error::print(&msg, self);
+        self.db.exit_status = 1;
+        false
+    }
+}
@@ -33,7 +33,7 @@ impl DataBase {
             params: vec![HashMap::new()],
             param_options: vec![HashMap::new()],
             position_parameters: vec![vec![]],
-            flags: "B".to_string(),
+            flags: "hB".to_string(),
             ..Default::default()
         };
 
@@ -110,6 +110,25 @@ impl DataBase {
         false
     }
 
+    /* for set -u; $@, $* and the special parameters are never unbound */
+    pub fn is_unset(&mut self, name: &str) -> bool {
+        if getter::special_param(self, name).is_some() || name == "@" || name == "*" {
+            return false;
+        }
+        if let Ok(n) = name.parse::<usize>() {
+            return self.position_parameters.last().is_none_or(|p| p.len() <= n);
+        }
+        ! self.has_value(name) && env::var(name).is_err()
+    }
+
+    pub fn is_unset_elem(&mut self, name: &str, key: &str) -> bool {
+        match getter::clone(self, name).as_mut() {
+            Some(_) if key == "@" || key == "*" => false,
+            Some(d) => ! d.has_key(key),
+            None => env::var(name).is_err() || key != "0",
+        }
+    }
+
     pub fn len(&mut self, key: &str) -> usize {
         match getter::clone(self, key).as_mut() {
             Some(d) => d.len(),
@@ -163,6 +182,10 @@ impl DataBase {
         }
     }
 
+    pub fn is_readonly(&mut self, name: &str) -> bool {
+        self.has_flag(name, 'r')
+    }
+
     pub fn get_target_layer(&mut self, name: &str, layer: Option<usize>) -> usize {
         match layer {
             Some(n) => n,
@@ -191,6 +214,14 @@ impl DataBase {
         SingleData::set_value(&mut self.params[layer], name, val)
     }
 
+    /* for the variables that only the shell updates */
+    pub fn set_readonly_param(&mut self, name: &str, val: &str) {
+        let _ = SingleData::set_value(&mut self.params[0], name, val);
+        if ! self.has_flag(name, 'r') {
+            self.set_flag(name, 'r');
+        }
+    }
+
     pub fn set_array_elem(&mut self, name: &str, val: &String, pos: usize, layer: Option<usize>) -> Result<(), ExecError> {
         Self::name_check(name)?;
         self.write_check(name)?;
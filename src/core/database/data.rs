@@ -56,6 +56,7 @@ pub trait Data {
     }
 
     fn get_all_as_array(&mut self) -> Result<Vec<String>, ExecError> {Err(ExecError::Other("not an array".to_string()))}
+    fn has_key(&mut self, key: &str) -> bool { key == "0" }
 
     fn is_special(&self) -> bool {false}
     fn is_single(&self) -> bool {false}
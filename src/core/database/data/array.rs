@@ -66,6 +66,10 @@ impl Data for ArrayData {
         self.body.get(&0).map(|v| Ok(v.clone())).ok_or(ExecError::Other("No entry".to_string()))?
     }
 
+    fn has_key(&mut self, key: &str) -> bool {
+        key.parse::<usize>().is_ok_and(|n| self.body.contains_key(&n))
+    }
+
     fn is_array(&self) -> bool {true}
     fn len(&mut self) -> usize { self.body.len() }
 }
@@ -24,6 +24,8 @@ pub fn initialize(db: &mut DataBase) -> Result<(), String> {
     SpecialData::get(db, "SECONDS");
 
     db.set_array("FUNCNAME", vec![], None)?;
+    db.set_array("BASH_SOURCE", vec![], None)?;
+    db.set_array("BASH_LINENO", vec![], None)?;
     Ok(())
 }
 
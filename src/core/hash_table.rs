@@ -0,0 +1,78 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::ShellCore;
+use crate::utils::{file, file_check};
+
+#[derive(Debug, Clone, Default)]
+pub struct HashEntry {
+    pub path: String,
+    pub hits: usize,
+}
+
+impl ShellCore {
+    /* The table is also discarded when PATH is changed without an assignment
+     * to it, for example by read or getopts. */
+    fn hash_check_path(&mut self) {
+        let path = self.db.get_param("PATH").unwrap_or_default();
+        if path != self.hash_path {
+            self.hash_table.clear();
+            self.hash_path = path;
+        }
+    }
+
+    pub fn hash_get(&mut self, name: &str) -> Option<String> {
+        self.hash_check_path();
+        let path = self.hash_table.get(name)?.path.clone();
+
+        if self.options.query("checkhash") && ! file_check::is_executable(&path) {
+            self.hash_table.remove(name);
+            return None;
+        }
+        Some(path)
+    }
+
+    pub fn hash_set(&mut self, name: &str, path: &str) {
+        self.hash_check_path();
+        let entry = HashEntry { path: path.to_string(), hits: 0 };
+        self.hash_table.insert(name.to_string(), entry);
+    }
+
+    /* returns the cached path or searches PATH and registers the result */
+    pub fn hash_search(&mut self, name: &str) -> Option<String> {
+        if let Some(path) = self.hash_get(name) {
+            return Some(path);
+        }
+
+        let path = file::search_command(name, &self.hash_path)?;
+        self.hash_set(name, &path);
+        Some(path)
+    }
+
+    /* called just before execution of an external command */
+    pub fn hash_use(&mut self, name: &str) -> Option<String> {
+        let path = self.hash_search(name)?;
+        if let Some(entry) = self.hash_table.get_mut(name) {
+            entry.hits += 1;
+        }
+        Some(path)
+    }
+
+    pub fn hash_clear(&mut self) {
+        self.hash_check_path();
+        self.hash_table.clear();
+    }
+
+    pub fn hash_remove(&mut self, name: &str) -> bool {
+        self.hash_check_path();
+        self.hash_table.remove(name).is_some()
+    }
+
+    pub fn hash_entries(&mut self) -> Vec<(String, HashEntry)> {
+        self.hash_check_path();
+        let mut ans: Vec<(String, HashEntry)> = self.hash_table.iter()
+                                      .map(|(k, v)| (k.clone(), v.clone())).collect();
+        ans.sort_by(|a, b| a.0.cmp(&b.0));
+        ans
+    }
+}
@@ -4,6 +4,7 @@
 use crate::ShellCore;
 use crate::error::exec::ExecError;
 use nix::unistd;
+use nix::errno::Errno;
 use nix::unistd::Pid;
 use nix::sys::signal;
 use nix::sys::wait;
@@ -16,7 +17,8 @@ pub struct JobEntry {
     proc_statuses: Vec<WaitStatus>,
     pub display_status: String,
     pub text: String,
-    change: bool,
+    pub change: bool,
+    pub no_hup: bool,
 }
 
 fn wait_nonblock(pid: &Pid, status: &mut WaitStatus) -> Result<(), ExecError> {
@@ -31,16 +33,27 @@ fn wait_nonblock(pid: &Pid, status: &mut WaitStatus) -> Result<(), ExecError> {
     Ok(())
 }
 
+/* a caught signal stops waiting so that its trap runs soon */
 fn wait_block(pid: &Pid, status: &mut WaitStatus) -> Result<i32, ExecError> {
-    *status = wait::waitpid(*pid, Some(WaitPidFlag::WUNTRACED))?;
-    let exit_status = match status {
+    loop {
+        match wait::waitpid(*pid, Some(WaitPidFlag::WUNTRACED)) {
+            Err(Errno::EINTR) if crate::signal::caught().is_some() => return Err(ExecError::Interrupted),
+            Err(Errno::EINTR) => {},
+            ws => {
+                *status = ws?;
+                return Ok(to_exit_status(status));
+            },
+        }
+    }
+}
+
+fn to_exit_status(status: &WaitStatus) -> i32 {
+    match status {
         WaitStatus::Exited(_, es) => *es,
         WaitStatus::Stopped(_, _) => 148,
         WaitStatus::Signaled(_, sig, _) => *sig as i32 + 128,
         _ => 1,
-    };
-
-    Ok(exit_status)
+    }
 }
 
 fn still(status: &WaitStatus) -> bool {
@@ -62,6 +75,24 @@ impl JobEntry {
             display_status: status.to_string(),
             text: text.to_string(),
             change: false,
+            no_hup: false,
+        }
+    }
+
+    pub fn pids(&self) -> &Vec<Pid> { &self.pids }
+
+    pub fn is_finished(&self) -> bool {
+        ! self.proc_statuses.iter().any(still)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.display_status == "Stopped"
+    }
+
+    pub fn exit_status(&self) -> i32 {
+        match self.proc_statuses.last() {
+            Some(s) => to_exit_status(s),
+            None    => 0,
         }
     }
 
@@ -99,13 +130,23 @@ impl JobEntry {
         Ok(exit_status)
     }
 
-    pub fn print(&self, priority: &Vec<usize>) {
-        if priority[0] == self.id {
-            println!("[{}]+  {}     {}", self.id, &self.display_status, &self.text);
+    pub fn print(&self, priority: &Vec<usize>, show_pid: bool) {
+        println!("{}", self.status_line(priority, show_pid));
+    }
+
+    pub fn status_line(&self, priority: &[usize], show_pid: bool) -> String {
+        let mark = if priority[0] == self.id {
+            "+"
         }else if priority.len() > 1 && priority[1] == self.id {
-            println!("[{}]-  {}     {}", self.id, &self.display_status, &self.text);
+            "-"
         }else {
-            println!("[{}]   {}     {}", self.id, &self.display_status, &self.text);
+            " "
+        };
+
+        match show_pid {
+            true  => format!("[{}]{} {}  {}     {}", self.id, mark, self.pids[0],
+                             &self.display_status, &self.text),
+            false => format!("[{}]{}  {}     {}", self.id, mark, &self.display_status, &self.text),
         }
     }
 
@@ -156,11 +197,40 @@ impl JobEntry {
     }
 
     pub fn send_cont(&mut self) {
-        for pid in &self.pids {
-            let _ = signal::kill(Pid::from_raw(-1 * i32::from(*pid)), signal::SIGCONT);            
+        let _ = self.kill(signal::SIGCONT as i32);
+    }
+
+    /* a job in the group of the shell (set +m) is signaled process by process */
+    fn kill(&self, sig: i32) -> Result<(), ExecError> {
+        let pgid = self.solve_pgid();
+        if pgid.as_raw() == 0 {
+            return Err(ExecError::Other("no such process".to_string()));
+        }
+
+        let targets = match pgid == unistd::getpgrp() {
+            true  => self.pids.iter().map(|p| p.as_raw()).collect(),
+            false => vec![-pgid.as_raw()],
+        };
+        let mut sent = false;
+        for t in targets {
+            sent |= unsafe { nix::libc::kill(t, sig) } == 0;
+        }
+
+        match sent {
+            true  => Ok(()),
+            false => Err(ExecError::Other(nix::errno::Errno::last().desc().to_string())),
         }
     }
 
+    pub fn send_signal(&mut self, sig: i32) -> Result<(), ExecError> {
+        self.kill(sig)?;
+
+        if self.display_status == "Stopped" && (sig == signal::SIGHUP as i32 || sig == signal::SIGTERM as i32) {
+            self.send_cont();
+        }
+        Ok(())
+    }
+
     pub fn solve_pgid(&self) -> Pid {
         for pid in &self.pids {
             match unistd::getpgid(Some(*pid)) {
@@ -181,9 +251,17 @@ impl ShellCore {
     }
 
     pub fn jobtable_print_status_change(&mut self) {
+        for line in self.jobtable_status_change() {
+            println!("{}", line);
+        }
+    }
+
+    /* returns the lines of the jobs whose status has changed and forgets finished jobs */
+    pub fn jobtable_status_change(&mut self) -> Vec<String> {
+        let mut lines = vec![];
         for e in self.job_table.iter_mut() {
             if e.change {
-                e.print(&self.job_table_priority);
+                lines.push(e.status_line(&self.job_table_priority, false));
                 e.change = false;
             }
         }
@@ -192,6 +270,7 @@ impl ShellCore {
 
         let ids = self.job_table.iter().map(|j| j.id).collect::<Vec<usize>>();
         self.job_table_priority.retain(|id| ids.contains(id) );
+        lines
     }
 
     pub fn generate_new_job_id(&self) -> usize {
@@ -1,47 +1,84 @@
 //SPDXFileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDXLicense-Identifier: BSD-3-Clause
 
-use std::collections::HashMap;
+use crate::ShellCore;
+
+/* The bool of each entry tells whether the option works. Unsupported options
+ * cannot be turned on. */
+
+/* options of set that have a letter, which is shown in $- */
+const FLAG_OPTIONS: [(char, &str, bool); 19] = [
+    ('a', "allexport", true), ('b', "notify", true), ('e', "errexit", true),
+    ('f', "noglob", true), ('h', "hashall", true), ('k', "keyword", true),
+    ('m', "monitor", true), ('n', "noexec", true), ('p', "privileged", true),
+    ('t', "onecmd", true), ('u', "nounset", true), ('v', "verbose", true),
+    ('x', "xtrace", true), ('B', "braceexpand", true), ('C', "noclobber", true),
+    ('E', "errtrace", true), ('H', "histexpand", false), ('P', "physical", true),
+    ('T', "functrace", true),
+];
+
+const SET_OPTIONS: [(&str, bool); 8] = [
+    ("emacs", false), ("history", false), ("ignoreeof", false),
+    ("interactive-comments", true), ("nolog", false), ("pipefail", true),
+    ("posix", false), ("vi", false),
+];
+
+const SHOPT_OPTIONS: [(&str, bool); 43] = [
+    ("autocd", true), ("cdable_vars", true), ("cdspell", true), ("checkhash", true),
+    ("checkjobs", false), ("checkwinsize", false), ("cmdhist", false), ("compat31", false),
+    ("compat32", false), ("compat40", false), ("compat41", false), ("dirspell", false),
+    ("dotglob", false), ("execfail", false), ("expand_aliases", false), ("extdebug", true),
+    ("extglob", true), ("extquote", false), ("failglob", false), ("force_fignore", false),
+    ("globstar", false), ("gnu_errfmt", false), ("histappend", false), ("histreedit", false),
+    ("histverify", false), ("hostcomplete", false), ("huponexit", false),
+    ("inherit_errexit", true), ("interactive_comments", false),
+    ("lastpipe", false), ("lithist", false), ("login_shell", false), ("mailwarn", false),
+    ("no_empty_cmd_completion", false), ("nocaseglob", false), ("nocasematch", false),
+    ("nullglob", false), ("progcomp", true), ("promptvars", false), ("restricted_shell", false),
+    ("shift_verbose", false), ("sourcepath", false), ("xpg_echo", false),
+];
+
+#[derive(Debug, Clone)]
+pub struct ShellOption {
+    pub name: String,
+    pub letter: Option<char>,
+    pub shopt: bool, // true for the options of shopt, false for the ones of set -o
+    pub supported: bool,
+    pub value: bool,
+}
 
 #[derive(Debug, Default)]
 pub struct Options {
-    opts: HashMap<String, bool>,
+    opts: Vec<ShellOption>,
 }
 
 impl Options {
-    pub fn new_as_basic_opts() -> Options {
-        let mut options = Options::default();
-        options.opts.insert("pipefail".to_string(), false);
-        options.opts.insert("noglob".to_string(), false);
-        options
-    }
-
-    pub fn new_as_shopts() -> Options {
+    pub fn new() -> Options {
         let mut options = Options::default();
-        let opt_strs = vec!["autocd", "cdable_vars", "cdspell", "checkhash",
-                   "checkjobs", "checkwinsize", "cmdhist", "compat31",
-                   "compat32", "compat40", "compat41", "dirspell",
-                   "dotglob", "execfail", "expand_aliases", "extdebug",
-                   "extglob", "extquote", "failglob", "force_fignore",
-                   "globstar", "gnu_errfmt", "histappend", "histreedit",
-                   "histverify", "hostcomplete", "huponexit", "interactive_comments",
-                   "lastpipe", "lithist", "login_shell", "mailwarn",
-                   "no_empty_cmd_completion", "nocaseglob", "nocasematch", "nullglob",
-                   "promptvars", "restricted_shell", "shift_verbose",
-                   "sourcepath", "xpg_echo"];
-
-        for opt in opt_strs {
-            options.opts.insert(opt.to_string(), false);
+        for (c, name, supported) in FLAG_OPTIONS {
+            options.add(name, Some(c), false, supported);
+        }
+        for (name, supported) in SET_OPTIONS {
+            options.add(name, None, false, supported);
         }
+        for (name, supported) in SHOPT_OPTIONS {
+            options.add(name, None, true, supported);
+        }
+        options.opts.sort_by(|a, b| a.name.cmp(&b.name));
 
-        let true_list = ["extglob", "progcomp"];
+        let true_list = ["braceexpand", "hashall", "interactive-comments", "extglob", "progcomp"];
         for opt in true_list {
-            options.opts.insert(opt.to_string(), true);
+            options.set(opt, true);
         }
 
         options
     }
 
+    fn add(&mut self, name: &str, letter: Option<char>, shopt: bool, supported: bool) {
+        let opt = ShellOption { name: name.to_string(), letter, shopt, supported, value: false };
+        self.opts.push(opt);
+    }
+
     pub fn format(opt: &str, onoff: bool) -> String {
         let onoff_str = match onoff {
             true  => "on",
@@ -50,75 +87,87 @@ impl Options {
 
         match opt.len() < 16 {
             true  => format!("{:16}{}", opt, onoff_str),
-            false => format!("{}\t{}", opt, onoff_str), 
+            false => format!("{}\t{}", opt, onoff_str),
         }
     }
 
-    pub fn format2(opt: &str, onoff: bool) -> String {
-        let onoff_str = match onoff {
-            true  => "-",
-            false => "+",
-        };
-
-        format!("set {}o {}", onoff_str, opt)
-    }
-
-    pub fn print_opt(&self, opt: &str) -> bool {
-        match self.opts.get_key_value(opt) {
-            None     => {
-                eprintln!("sush: shopt: {}: invalid shell option name", opt);
-                false
-            },
-            Some(kv) => {
-                println!("{}", Self::format(kv.0, *kv.1));
-                true
-            },
+    /* the format of set +o and shopt -p, which can be read as commands */
+    pub fn format2(opt: &ShellOption) -> String {
+        match (opt.shopt, opt.value) {
+            (true, true)   => format!("shopt -s {}", opt.name),
+            (true, false)  => format!("shopt -u {}", opt.name),
+            (false, true)  => format!("set -o {}", opt.name),
+            (false, false) => format!("set +o {}", opt.name),
         }
     }
 
-    pub fn print_all(&self, positive: bool) {
-        let f = match positive {
-            true => Self::format,
-            false => Self::format2,
-        };
+    pub fn get(&self, opt: &str) -> Option<&ShellOption> {
+        self.opts.iter().find(|o| o.name == opt)
+    }
 
-        let mut list = self.opts.iter()
-                       .map(|opt| f(opt.0, *opt.1))
-                       .collect::<Vec<String>>();
+    /* the options of shopt (shopt == true) or set -o (shopt == false) in alphabetical order */
+    pub fn list(&self, shopt: bool) -> Vec<ShellOption> {
+        self.opts.iter().filter(|o| o.shopt == shopt).cloned().collect()
+    }
 
-        list.sort();
-        list.iter().for_each(|e| println!("{}", e));
+    pub fn exist(&self, opt: &str, shopt: bool) -> bool {
+        self.get(opt).is_some_and(|o| o.shopt == shopt)
     }
 
-    pub fn print_if(&self, onoff: bool) {
-        let mut list = self.opts.iter()
-                       .filter(|opt| *opt.1 == onoff)
-                       .map(|opt| Self::format(opt.0, *opt.1))
-                       .collect::<Vec<String>>();
+    pub fn is_supported(&self, opt: &str) -> bool {
+        self.get(opt).is_some_and(|o| o.supported)
+    }
 
-        list.sort();
-        list.iter().for_each(|e| println!("{}", e));
+    pub fn query(&self, opt: &str) -> bool {
+        self.get(opt).is_some_and(|o| o.value)
     }
 
-    pub fn exist(&self, opt: &str) -> bool {
-        self.opts.contains_key(opt)
+    pub fn name_of(&self, letter: char) -> Option<String> {
+        self.opts.iter().find(|o| o.letter == Some(letter)).map(|o| o.name.clone())
     }
 
-    pub fn query(&self, opt: &str) -> bool {
-        self.exist(opt) && self.opts[opt]
+    pub fn letter_of(&self, opt: &str) -> Option<char> {
+        self.get(opt).and_then(|o| o.letter)
     }
 
     pub fn set(&mut self, opt: &str, onoff: bool) -> bool {
-        if ! self.opts.contains_key(opt) {
-            eprintln!("sush: shopt: {}: invalid shell option name", opt);
+        match self.opts.iter_mut().find(|o| o.name == opt) {
+            Some(o) => {o.value = onoff; true},
+            None    => false,
+        }
+    }
+}
+
+impl ShellCore {
+    /* every change of an option goes through here so that $- follows the registry */
+    pub fn set_option(&mut self, name: &str, onoff: bool) -> bool {
+        if ! self.options.set(name, onoff) {
             return false;
         }
 
-        self.opts.insert(opt.to_string(), onoff);
+        if let Some(c) = self.options.letter_of(name) {
+            if ! onoff {
+                self.db.flags.retain(|f| f != c);
+            }else if ! self.db.flags.contains(c) {
+                self.db.flags.push(c);
+            }
+        }
         true
     }
 
-    pub fn get_keys(&self) -> Vec<String> {
-        self.opts.clone().into_keys().collect()
+    pub fn set_flag_option(&mut self, letter: char, onoff: bool) -> bool {
+        match self.options.name_of(letter) {
+            Some(name) => self.set_option(&name, onoff),
+            None       => false,
+        }
+    }
+
+    /* SHELLOPTS and BASHOPTS are readonly lists of the enabled options */
+    pub fn update_shellopts(&mut self) {
+        for (name, shopt) in [("SHELLOPTS", false), ("BASHOPTS", true)] {
+            let list = self.options.list(shopt).into_iter()
+                       .filter(|o| o.value).map(|o| o.name).collect::<Vec<String>>();
+            self.db.set_readonly_param(name, &list.join(":"));
+        }
     }
 }
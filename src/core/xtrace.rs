@@ -0,0 +1,72 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::{Feeder, ShellCore};
+use crate::elements::word::Word;
+use nix::unistd;
+use std::os::fd::{BorrowedFd, RawFd};
+
+/* words of a trace are quoted so that it can be run as a command */
+pub fn quote(s: &str) -> String {
+    if s.is_empty() {
+        return "''".to_string();
+    }
+
+    let special = |(i, c): (usize, char)| " \t\n'\"\\|&;()<>!{}*[?]^$`".contains(c)
+                                          || (i == 0 && (c == '~' || c == '#'));
+    match s.chars().enumerate().any(special) {
+        true  => format!("'{}'", s.replace('\'', "'\\''")),
+        false => s.to_string(),
+    }
+}
+
+pub fn quote_words(words: &[String]) -> String {
+    words.iter().map(|w| quote(w)).collect::<Vec<String>>().join(" ")
+}
+
+impl ShellCore {
+    /* PS4 is expanded like a double-quoted string without being traced itself.
+     * Its first character is repeated once per level of command substitution,
+     * eval and source. */
+    pub fn get_ps4(&mut self) -> String {
+        let raw = self.db.get_param("PS4").unwrap_or_default();
+        let exit_status = self.db.exit_status;
+        let command_substituted = self.command_substituted;
+        self.set_option("xtrace", false);
+
+        let mut feeder = Feeder::new(&format!("\"{}\"", raw.replace('"', "\\\"")));
+        let ps4 = match Word::parse(&mut feeder, self, false) {
+            Ok(Some(w)) if feeder.len() == 0 => w.eval_for_case_word(self).unwrap_or(raw),
+            _ => raw,
+        };
+
+        self.set_option("xtrace", true);
+        self.db.exit_status = exit_status;
+        self.command_substituted = command_substituted;
+
+        let depth = (self.source_level + self.eval_level + self.comsub_level) as usize;
+        match ps4.chars().next() {
+            Some(c) => c.to_string().repeat(depth) + &ps4,
+            None    => ps4,
+        }
+    }
+
+    /* writes a line of set -x to the fd in BASH_XTRACEFD, or to stderr */
+    pub fn xtrace(&mut self, line: &str) {
+        if ! self.db.flags.contains('x') {
+            return;
+        }
+
+        let text = format!("{}{}\n", self.get_ps4(), line);
+        let fd = self.db.get_param("BASH_XTRACEFD").unwrap_or_default()
+                 .parse::<RawFd>().ok().filter(|fd| *fd >= 0);
+
+        if let Some(fd) = fd {
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            if unistd::write(fd, text.as_bytes()).is_ok() {
+                return;
+            }
+        }
+        eprint!("{}", text);
+    }
+}
@@ -87,6 +87,7 @@ pub trait Command {
 
         if result.is_ok() {
             let _ = self.run(core, false);
+            core.judged_inside = self.judged_inside();
         }else{
             core.db.exit_status = 1;
         }
@@ -100,6 +101,8 @@ pub trait Command {
     fn set_force_fork(&mut self);
     fn boxed_clone(&self) -> Box<dyn Command>;
     fn force_fork(&self) -> bool;
+    /* true if set -e and the ERR trap judge the commands inside instead of this one */
+    fn judged_inside(&self) -> bool { false }
 }
 
 pub fn eat_inner_script(feeder: &mut Feeder, core: &mut ShellCore, left: &str, right: Vec<&str>,
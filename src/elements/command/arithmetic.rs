@@ -49,6 +49,7 @@ impl ArithmeticCommand {
     pub fn eval(&mut self, core: &mut ShellCore) -> Result<String, ExecError> {
         let mut ans = String::new();
         for a in &mut self.expressions {
+            core.xtrace(&format!("(( {} ))", a.text));
             ans = a.eval(core)?;
         }
         Ok(ans)
@@ -30,6 +30,7 @@ impl Command for BraceCommand {
     fn set_force_fork(&mut self) { self.force_fork = true; }
     fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
     fn force_fork(&self) -> bool { self.force_fork }
+    fn judged_inside(&self) -> bool { true }
 }
 
 impl BraceCommand {
@@ -23,17 +23,14 @@ impl Command for CaseCommand {
         let mut next = false;
         let word = self.word.clone().unwrap();
 
-        if core.db.flags.contains('x') {
-            let ps4 = core.get_ps4();
-            eprintln!("\r{} case {} in\r", ps4, word.text);
-        }
+        core.xtrace(&format!("case {} in", word.text));
 
         let w = match word.eval_for_case_word(core) {
             Some(w) => w, 
             _       => "".to_string(),
         };
 
-        let extglob = core.shopts.query("extglob");
+        let extglob = core.options.query("extglob");
 
         for e in &mut self.patterns_script_end {
             for pattern in &mut e.0 {
@@ -60,6 +57,7 @@ impl Command for CaseCommand {
     fn set_force_fork(&mut self) { self.force_fork = true; }
     fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
     fn force_fork(&self) -> bool { self.force_fork }
+    fn judged_inside(&self) -> bool { true }
 }
 
 impl CaseCommand {
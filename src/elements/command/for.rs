@@ -50,9 +50,21 @@ impl Command for ForCommand {
     fn set_force_fork(&mut self) { self.force_fork = true; }
     fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
     fn force_fork(&self) -> bool { self.force_fork }
+    fn judged_inside(&self) -> bool { true }
 }
 
 impl ForCommand {
+    /* the part traced by set -x at each loop */
+    fn header(&self) -> String {
+        match self.has_in {
+            true  => {
+                let values = self.values.iter().map(|w| w.text.clone()).collect::<Vec<String>>();
+                format!("for {} in {}", self.name, values.join(" "))
+            },
+            false => format!("for {} in \"$@\"", self.name),
+        }
+    }
+
     fn eval_values(&mut self, core: &mut ShellCore) -> Option<Vec<String>> {
         let mut ans = vec![];
         for w in &mut self.values {
@@ -82,6 +94,8 @@ impl ForCommand {
                 return false;
             }
 
+            core.xtrace(&self.header());
+
             if let Err(e) = core.db.set_param(&self.name, &p, None) {
                 core.db.exit_status = 1;
                 let msg = format!("{:?}", &e);
@@ -108,6 +122,7 @@ impl ForCommand {
             return (true, "1".to_string());
         }
 
+        core.xtrace(&format!("(( {} ))", a.as_ref().unwrap().text.trim_start()));
         match a.clone().unwrap().eval(core) {
             Ok(n) => return (true, n),
             _     => return (false, "0".to_string()), 
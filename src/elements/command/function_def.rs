@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{ShellCore, Feeder};
+use crate::{signal, ShellCore, Feeder};
 use crate::error::exec::ExecError;
 use crate::error::parse::ParseError;
 use super::{Command, Pipe, Redirect};
@@ -20,6 +20,7 @@ fn reserved(w: &str) -> bool {
 pub struct FunctionDefinition {
     pub text: String,
     name: String,
+    source: String,
     command: Option<Box<dyn Command>>,
     redirects: Vec<Redirect>,
     force_fork: bool,
@@ -32,6 +33,7 @@ impl Command for FunctionDefinition {
         }
 
         core.db.functions.insert(self.name.to_string(), self.clone());
+        core.db.exit_status = 0;
         Ok(None)
     }
 
@@ -48,6 +50,7 @@ impl FunctionDefinition {
         FunctionDefinition {
             text: String::new(),
             name: String::new(),
+            source: String::new(),
             command: None,
             redirects: vec![],
             force_fork: false,
@@ -56,9 +59,10 @@ impl FunctionDefinition {
 
     pub fn run_as_command(&mut self, args: &mut Vec<String>, core: &mut ShellCore)
         -> Result<Option<Pid>, ExecError> {
-        let mut array = core.db.get_array_all("FUNCNAME");
-        array.insert(0, args[0].clone());
-        let _ = core.db.set_array("FUNCNAME", array, None);
+        if ! core.check_funcnest(&args[0]) {
+            return Ok(None);
+        }
+        core.push_call_stack(&args[0], &self.source, &args[1..]);
 
         let len = core.db.position_parameters.len();
         args[0] = core.db.position_parameters[len-1][0].clone();
@@ -67,18 +71,20 @@ impl FunctionDefinition {
         let mut dummy = Pipe::new("|".to_string());
 
         core.source_function_level += 1;
+        core.function_level += 1;
+        let hidden_traps = signal::enter_function(core);
         let pid = self.command.clone()
                         .unwrap()
                         .exec(core, &mut dummy);
         core.return_flag = false;
+        signal::leave_function(core, hidden_traps);
+        core.function_level -= 1;
         core.source_function_level -= 1;
 
         core.db.position_parameters.pop();
 
         //core.db.set_param("#", &number);, None, None
-        let mut array = core.db.get_array_all("FUNCNAME");
-        array.remove(0);
-        let _ = core.db.set_array("FUNCNAME", array, None);
+        core.pop_call_stack();
         pid
     }
 
@@ -113,6 +119,7 @@ impl FunctionDefinition {
 
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
         let mut ans = Self::new();
+        ans.source = core.current_source();
         feeder.set_backup();
 
         if feeder.starts_with("function") {
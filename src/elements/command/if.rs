@@ -20,17 +20,19 @@ pub struct IfCommand {
 
 impl Command for IfCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
+        let susp_e_option = core.suspend_e_option;
         for i in 0..self.if_elif_scripts.len() {
+            core.suspend_e_option = true;
             let _ = self.if_elif_scripts[i].exec(core);
+            core.suspend_e_option = susp_e_option;
             if core.db.exit_status == 0 {
                 let _ = self.then_scripts[i].exec(core);
                 return Ok(());
             }
         }
 
-        match self.else_script.as_mut() {
-            Some(s) => s.exec(core)?,
-            _ => {},
+        if let Some(s) = self.else_script.as_mut() {
+            s.exec(core)?;
         }
         Ok(())
     }
@@ -40,6 +42,7 @@ impl Command for IfCommand {
     fn set_force_fork(&mut self) { self.force_fork = true; }
     fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
     fn force_fork(&self) -> bool { self.force_fork }
+    fn judged_inside(&self) -> bool { true }
 }
 
 impl IfCommand {
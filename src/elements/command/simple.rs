@@ -3,10 +3,11 @@
 
 pub mod parser;
 
-use crate::{proc_ctrl, ShellCore};
+use crate::{proc_ctrl, signal, Feeder, ShellCore};
+use crate::core::xtrace;
 
 use crate::error::exec::ExecError;
-use crate::utils::exit;
+use crate::utils::{exit, file_check};
 use super::{Command, Pipe, Redirect};
 use crate::elements::substitution::Substitution;
 use crate::elements::word::Word;
@@ -24,18 +25,29 @@ pub struct SimpleCommand {
     substitutions_as_args: Vec<Substitution>,
     permit_substitution_arg: bool,
     lineno: usize,
+    hashed_path: Option<String>,
+    keywords: Vec<Substitution>,
 }
 
 
 impl Command for SimpleCommand {
     fn exec(&mut self, core: &mut ShellCore, pipe: &mut Pipe) -> Result<Option<Pid>, ExecError> {
-        core.db.set_param("LINENO", &self.lineno.to_string(), None)?;
+        /* traps of DEBUG, ERR and RETURN see the line of the command that fired them */
+        if ! core.running_trap {
+            core.db.set_param("LINENO", &self.lineno.to_string(), None)?;
+        }
         if Self::break_continue_or_return(core) {
             return Ok(None);
         }
 
+        if ! core.running_trap {
+            let _ = core.db.set_param("BASH_COMMAND", self.text.trim_end(), None);
+            signal::run_pseudo_trap(core, signal::DEBUG);
+        }
+
         self.args.clear();
         let mut words = self.words.to_vec();
+        self.take_keywords(&mut words, core);
         if ! words.iter_mut().all(|w| self.set_arg(w, core).is_ok()){
             return Err(ExecError::Other("word evaluation error".to_string()));
         }
@@ -50,6 +62,7 @@ impl Command for SimpleCommand {
         core.db.push_local();
         let layer = core.db.get_layer_num()-1;
         let _ = self.set_local_params(core, layer);
+        core.xtrace(&xtrace::quote_words(&self.args));
 
         if core.db.functions.contains_key(&self.args[0]) {
             let mut f = core.db.functions[&self.args[0]].clone();
@@ -60,7 +73,7 @@ impl Command for SimpleCommand {
             core.run_builtin(&mut self.args, &mut special_args)?;
         } else {
             let _ = self.set_environment_variables(core);
-            proc_ctrl::exec_command(&self.args, core);
+            proc_ctrl::exec_command(&self.args, core, &self.hashed_path);
         }
 
         core.db.pop_local();
@@ -87,18 +100,40 @@ impl SimpleCommand {
         Self::check_sigint(core)?;
 
         core.db.last_arg = self.args.last().unwrap().clone();
-        self.option_x_output(core);
 
-        if self.force_fork 
-        || pipe.is_connected() 
-        || ( ! core.builtins.contains_key(&self.args[0]) 
-           && ! core.db.functions.contains_key(&self.args[0]) ) {
+        let external = ! core.builtins.contains_key(&self.args[0])
+                       && ! core.db.functions.contains_key(&self.args[0])
+                       && ! self.autocd(core);
+        self.set_hashed_path(core, external);
+
+        if self.force_fork || pipe.is_connected() || external {
             self.fork_exec(core, pipe)
         }else{
             self.nofork_exec(core)
         }
     }
 
+    /* a directory name given as a command is the argument of cd with autocd */
+    fn autocd(&mut self, core: &mut ShellCore) -> bool {
+        if ! core.db.flags.contains('i') || ! core.options.query("autocd")
+        || ! file_check::is_dir(&self.args[0]) {
+            return false;
+        }
+
+        self.args.insert(0, "--".to_string());
+        self.args.insert(0, "cd".to_string());
+        true
+    }
+
+    fn set_hashed_path(&mut self, core: &mut ShellCore, external: bool) {
+        self.hashed_path = None;
+        if ! external || ! core.db.flags.contains('h') || self.args[0].contains('/')
+        || self.substitutions.iter().any(|s| s.text.starts_with("PATH=")) {
+            return;
+        }
+        self.hashed_path = core.hash_use(&self.args[0]);
+    }
+
     fn check_sigint(core: &mut ShellCore) -> Result<(), ExecError> {
         if core.sigint.load(Relaxed) {
             core.db.exit_status = 130;
@@ -109,23 +144,54 @@ impl SimpleCommand {
 
     fn exec_set_param(&mut self, core: &mut ShellCore) -> Result<Option<Pid>, ExecError> {
         core.db.last_arg = String::new();
-        self.option_x_output(core);
-        
-        self.substitutions.iter_mut()
-            .for_each(|s| {let _ = s.eval(core, None, false);});
+
+        /* the exit status is of the last command substitution or zero */
+        core.command_substituted = false;
+        let mut failed = false;
+        for s in self.substitutions.iter_mut() {
+            if let Err(e) = s.eval(core, None, false) {
+                if let ExecError::VariableReadOnly(_) = e {
+                    e.print(core);
+                }
+                failed = true;
+            }
+        }
+        if ! failed && ! core.command_substituted {
+            core.db.exit_status = 0;
+        }
 
         Ok(None)
     }
 
+    /* set -k: assignments after the command name are also for the command */
+    fn take_keywords(&mut self, words: &mut Vec<Word>, core: &mut ShellCore) {
+        self.keywords.clear();
+        if ! core.db.flags.contains('k') {
+            return;
+        }
+
+        let mut i = 1;
+        while i < words.len() {
+            let mut feeder = Feeder::new(&words[i].text);
+            match Substitution::parse(&mut feeder, core) {
+                Ok(Some(s)) if feeder.len() == 0 => {
+                    self.keywords.push(s);
+                    words.remove(i);
+                },
+                _ => i += 1,
+            }
+        }
+    }
+
     fn set_local_params(&mut self, core: &mut ShellCore, layer: usize) -> Result<(), ExecError> {
-        for s in self.substitutions.iter_mut() {
+        for s in self.substitutions.iter_mut().chain(self.keywords.iter_mut()) {
             s.eval(core, Some(layer), false)?;
         }
         Ok(())
     }
 
     fn set_environment_variables(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
-        for s in self.substitutions.iter_mut() {
+        for s in self.substitutions.iter_mut().chain(self.keywords.iter_mut()) {
             s.eval(core, None, true)?;
         }
         Ok(())
@@ -146,28 +212,4 @@ impl SimpleCommand {
             },
         }
     }
-
-    fn option_x_output(&self, core: &mut ShellCore) {
-        if ! core.db.flags.contains('x') {
-            return;
-        }
-
-        let ps4 = core.get_ps4();
-        for s in &self.substitutions {
-            eprintln!("\r{} {}\r", &ps4, &s.text);
-        }
-
-        if self.args.is_empty() {
-            return;
-        }
-
-        eprint!("{}", &ps4);
-        for a in &self.args {
-            match a.contains(" "){
-                false => eprint!(" {}", &a),
-                true  => eprint!(" '{}'", &a),
-            }
-        }
-        eprintln!("");
-    }
 }
@@ -87,6 +87,7 @@ impl SimpleCommand {
 
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
         let mut ans = Self::default();
+        ans.lineno = feeder.lineno;
         feeder.set_backup();
 
         while Self::eat_substitution(feeder, &mut ans, core)? {
@@ -19,11 +19,12 @@ pub struct WhileCommand {
 impl Command for WhileCommand {
     fn run(&mut self, core: &mut ShellCore, _: bool) -> Result<(), ExecError> {
         core.loop_level += 1;
+        let susp_e_option = core.suspend_e_option;
         loop {
             core.suspend_e_option = true;
             let _ = self.while_script.as_mut().unwrap().exec(core);
 
-            core.suspend_e_option = false;
+            core.suspend_e_option = susp_e_option;
             if core.db.exit_status != 0 {
                 core.db.exit_status = 0;
                 break;
@@ -53,6 +54,7 @@ impl Command for WhileCommand {
     fn set_force_fork(&mut self) { self.force_fork = true; }
     fn boxed_clone(&self) -> Box<dyn Command> {Box::new(self.clone())}
     fn force_fork(&self) -> bool { self.force_fork }
+    fn judged_inside(&self) -> bool { true }
 }
 
 impl WhileCommand {
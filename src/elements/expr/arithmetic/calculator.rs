@@ -5,7 +5,7 @@ use crate::ShellCore;
 use crate::error::exec::ExecError;
 use crate::utils::exit;
 use super::elem::ArithElem;
-use super::{float, int, rev_polish, trenary, word, array_elem};
+use super::{ArithmeticExpr, float, int, rev_polish, trenary, word, array_elem};
 
 pub fn pop_operand(stack: &mut Vec<ArithElem>, core: &mut ShellCore) -> Result<ArithElem, ExecError> {
     match stack.pop() {
@@ -63,6 +63,7 @@ pub fn calculate(elements: &Vec<ArithElem>, core: &mut ShellCore) -> Result<Arit
         return Ok(ArithElem::Integer(0));
     }
 
+    check_operands(elements)?;
     let rev_pol = rev_polish::rearrange(elements)?;
     dry_run(&rev_pol)?;
 
@@ -100,6 +101,41 @@ pub fn calculate(elements: &Vec<ArithElem>, core: &mut ShellCore) -> Result<Arit
     pop_operand(&mut stack, core)
 }
 
+/* finds a binary operator without its right operand in the infix order
+ * so that the error token is the one where the expression breaks */
+fn check_operands(elements: &[ArithElem]) -> Result<(), ExecError> {
+    let mut expect_operand = false;
+    let mut last_op = String::new();
+
+    for e in elements {
+        match e {
+            ArithElem::BinaryOp(_) => {
+                if expect_operand {
+                    return Err( ExecError::OperandExpected(e.to_string()));
+                }
+                expect_operand = true;
+                last_op = e.to_string();
+            },
+            ArithElem::Ternary(left, right) => { // a ternary contains its operands
+                let empty = |e: &Option<ArithmeticExpr>| e.as_ref().is_none_or(|a| a.text.trim().is_empty());
+                if empty(left) {
+                    return Err( ExecError::OperandExpected("?".to_string()));
+                }
+                if empty(right) {
+                    return Err( ExecError::OperandExpected(":".to_string()));
+                }
+            },
+            ArithElem::UnaryOp(_) | ArithElem::Increment(_) | ArithElem::Delimiter(_) => {},
+            _ => expect_operand = false,
+        }
+    }
+
+    match expect_operand {
+        true  => Err( ExecError::OperandExpected(last_op)),
+        false => Ok(()),
+    }
+}
+
 fn dry_run(rev_pol: &Vec<ArithElem>) -> Result<(), ExecError> {
     let mut stack = vec![];
 
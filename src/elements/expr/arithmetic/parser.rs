@@ -38,14 +38,14 @@ impl ArithmeticExpr {
         true
     }
 
-    fn eat_conditional_op(feeder: &mut Feeder,
-        ans: &mut Self, core: &mut ShellCore) -> Result<bool, ParseError> {
+    fn eat_conditional_op(feeder: &mut Feeder, ans: &mut Self,
+        core: &mut ShellCore, addline: bool) -> Result<bool, ParseError> {
         if ! feeder.starts_with("?") {
             return Ok(false);
         }
 
         ans.text += &feeder.consume(1);
-        let left = Self::parse(feeder, core, true)?;
+        let left = Self::parse(feeder, core, addline)?;
         if left.is_some() {
             ans.text += &left.as_ref().unwrap().text;
         }
@@ -56,7 +56,7 @@ impl ArithmeticExpr {
         }
 
         ans.text += &feeder.consume(1);
-        let right = Self::parse(feeder, core, true)?;
+        let right = Self::parse(feeder, core, addline)?;
         if right.is_some() {
             ans.text += &right.as_ref().unwrap().text;
         }
@@ -199,7 +199,7 @@ impl ArithmeticExpr {
             }
 
             if Self::eat_output_format(feeder, &mut ans, core) 
-            || Self::eat_conditional_op(feeder, &mut ans, core)?
+            || Self::eat_conditional_op(feeder, &mut ans, core, addline)?
             || Self::eat_incdec(feeder, &mut ans) 
             || Self::eat_unary_operator(feeder, &mut ans, core)
             || Self::eat_paren(feeder, core, &mut ans)?
@@ -51,6 +51,9 @@ pub fn str_to_num(name: &str, core: &mut ShellCore) -> Result<ArithElem, ExecErr
 
     for i in 0..RESOLVE_LIMIT {
         match utils::is_name(&name, core) {
+            true if core.db.flags.contains('u') && core.db.is_unset(&name) => {
+                return Err(exit::unbound_variable(&name, core));
+            },
             true  => name = core.db.get_param(&name)?,
             false => break,
         }
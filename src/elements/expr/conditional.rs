@@ -67,7 +67,10 @@ impl ConditionalExpr {
         let mut stack = Self::reduce(&rev_pol, core)?;
     
         match pop_operand(&mut stack, core) {
-            Ok(CondElem::Operand(s))  => Ok(CondElem::Ans(s.len() > 0)), //for [[ string ]]
+            Ok(CondElem::Operand(s))  => { //for [[ string ]]
+                core.xtrace(&format!("[[ -n {} ]]", &s));
+                Ok(CondElem::Ans(s.len() > 0))
+            },
             other_ans             => other_ans,
         }
     }
@@ -153,9 +156,10 @@ impl ConditionalExpr {
             Err(e) => return Err(e),
         };
 
+        core.xtrace(&format!("[[ {} {} ]]", op, &operand));
         if op == "-o" || op == "-v" || op == "-z" || op == "-n" {
             let ans = match op {
-                "-o" => core.options.query(&operand),
+                "-o" => core.options.exist(&operand, false) && core.options.query(&operand),
                 //"-v" => core.db.get_value(&operand).is_some() || env::var(&operand).is_ok(),
                 "-v" => core.db.has_value(&operand) || env::var(&operand).is_ok(),
                 "-z" => operand.is_empty(),
@@ -188,6 +192,7 @@ impl ConditionalExpr {
             None  => return Err(ExecError::Other("Invalid operand".to_string())),
         };
 
+        core.xtrace(&format!("[[ {} =~ {} ]]", &left, &right_eval));
         let re = match Regex::new(&right_eval) {
             Ok(regex) => regex,
             Err(e) => return Err(ExecError::Other(e.to_string())),
@@ -211,7 +216,8 @@ impl ConditionalExpr {
             Err(e) => return Err(e),
         };
 
-        let extglob = core.shopts.query("extglob");
+        core.xtrace(&format!("[[ {} {} {} ]]", &left, op, &right));
+        let extglob = core.options.query("extglob");
         if op.starts_with("=") || op == "!=" || op == "<" || op == ">" {
             let ans = match op {
                 "==" | "=" => glob::parse_and_compare(&left, &right, extglob),
@@ -22,6 +22,9 @@ pub fn replace(from: RawFd, to: RawFd) -> bool {
     if from < 0 || to < 0 {
         return false;
     }
+    if from == to {
+        return true;
+    }
 
     match unistd::dup2(from, to) {
         Ok(_) => {
@@ -52,8 +55,11 @@ fn share(from: RawFd, to: RawFd) -> Result<(), ExecError> {
 }
 
 pub fn backup(from: RawFd) -> RawFd {
-    fcntl::fcntl(from, fcntl::F_DUPFD_CLOEXEC(10))
-           .expect("Can't allocate fd for backup")
+    match fcntl::fcntl(from, fcntl::F_DUPFD_CLOEXEC(10)) {
+        Ok(fd) => fd,
+        Err(Errno::EBADF) => -1, //not opened
+        Err(_) => panic!("Can't allocate fd for backup"),
+    }
 }
 
 pub fn connect(pipe: &mut Pipe, rs: &mut Vec<Redirect>, core: &mut ShellCore) -> Result<(), ExecError> {
@@ -4,6 +4,7 @@
 use std::fs::{File, OpenOptions};
 use std::os::fd::{IntoRawFd, RawFd};
 use std::io::Error;
+use std::path::Path;
 use crate::{Feeder, ShellCore};
 use crate::elements::io;
 use crate::elements::word::Word;
@@ -29,7 +30,7 @@ pub struct Redirect {
 impl Redirect {
     pub fn connect(&mut self, restore: bool, core: &mut ShellCore) -> Result<(), ExecError> {
         if self.symbol == "<<<" {
-            return self.redirect_herestring(core);
+            return self.redirect_herestring(restore, core);
         }
 
         let args = self.right.eval(core)?;
@@ -39,9 +40,15 @@ impl Redirect {
 
         self.right.text = args[0].clone();
 
+        if (self.symbol == ">" || self.symbol == "&>")
+        && core.options.query("noclobber") && Path::new(&self.right.text).is_file() {
+            let msg = format!("{}: cannot overwrite existing file", &self.right.text);
+            return Err(ExecError::Other(msg));
+        }
+
         match self.symbol.as_str() {
             "<" => self.redirect_simple_input(restore), // < 
-            ">" => self.redirect_simple_output(restore), // > 
+            ">" | ">|" => self.redirect_simple_output(restore), // > 
             ">&" => self.redirect_output_fd(restore), // >&2
             ">>" => self.redirect_append(restore),
             "&>" => self.redirect_both_output(restore),
@@ -56,12 +63,12 @@ impl Redirect {
         }
     }
 
-    fn connect_to_file(&mut self, file_open_result: Result<File,Error>, restore: bool) -> bool {
+    fn connect_to_file(&mut self, open: impl FnOnce(&str) -> Result<File,Error>, restore: bool) -> bool {
         if restore {
             self.left_backup = io::backup(self.left_fd);
         }
 
-        match file_open_result {
+        match open(&self.right.text) {
             Ok(file) => {
                 let fd = file.into_raw_fd();
                 let result = io::replace(fd, self.left_fd);
@@ -80,7 +87,7 @@ impl Redirect {
 
     fn redirect_simple_input(&mut self, restore: bool) -> Result<(), ExecError> {
         self.set_left_fd(0);
-        if ! self.connect_to_file(File::open(&self.right.text), restore) {
+        if ! self.connect_to_file(|f| File::open(f), restore) {
             return Err(ExecError::Other("file error".to_string()));
         }
         Ok(())
@@ -88,7 +95,7 @@ impl Redirect {
 
     fn redirect_simple_output(&mut self, restore: bool) -> Result<(), ExecError> {
         self.set_left_fd(1);
-        if ! self.connect_to_file(File::create(&self.right.text), restore) {
+        if ! self.connect_to_file(|f| File::create(f), restore) {
             return Err(ExecError::Other("file error".to_string()));
         }
         Ok(())
@@ -110,8 +117,8 @@ impl Redirect {
 
     fn redirect_append(&mut self, restore: bool) -> Result<(), ExecError> {
         self.set_left_fd(1);
-        if ! self.connect_to_file(OpenOptions::new().create(true)
-                .write(true).append(true).open(&self.right.text), restore) {
+        if ! self.connect_to_file(|f| OpenOptions::new().create(true)
+                .write(true).append(true).open(f), restore) {
             return Err(ExecError::Other("file error".to_string()));
         }
         Ok(())
@@ -119,7 +126,7 @@ impl Redirect {
 
     fn redirect_both_output(&mut self, restore: bool) -> Result<(), ExecError> {
         self.left_fd = 1;
-        if ! self.connect_to_file(File::create(&self.right.text), restore){
+        if ! self.connect_to_file(|f| File::create(f), restore){
             return Err(ExecError::Other("file error".to_string()));
         }
 
@@ -129,7 +136,7 @@ impl Redirect {
         io::share(1, 2)
     }
 
-    fn redirect_herestring(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
+    fn redirect_herestring(&mut self, restore: bool, core: &mut ShellCore) -> Result<(), ExecError> {
         let (r, s) = unistd::pipe().expect("Cannot open pipe");
         let recv = r.into_raw_fd();
         let send = s.into_raw_fd();
@@ -148,6 +155,10 @@ impl Redirect {
             },
             ForkResult::Parent { child: _ } => {
                 io::close(send, "herestring close error (parent send)");
+                self.left_fd = 0;
+                if restore {
+                    self.left_backup = io::backup(0);
+                }
                 io::replace(recv, 0);
             },
         }
@@ -157,6 +168,8 @@ impl Redirect {
     pub fn restore(&mut self) {
         if self.left_backup >= 0 && self.left_fd >= 0 {
             io::replace(self.left_backup, self.left_fd);
+        }else if self.left_fd >= 0 { //the fd was not opened before the redirect
+            let _ = unistd::close(self.left_fd);
         }
         if self.extra_left_backup >= 0 {
             io::replace(self.extra_left_backup, 2);
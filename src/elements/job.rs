@@ -21,7 +21,8 @@ pub struct Job {
 
 impl Job {
     pub fn exec(&mut self, core: &mut ShellCore, bg: bool) -> Result<(), ExecError> {
-        let pgid = match core.is_subshell {
+        /* without job control (set +m), jobs stay in the group of the shell */
+        let pgid = match core.is_subshell || ! core.db.flags.contains('m') {
             true  => unistd::getpgrp(),
             false => Pid::from_raw(0),
         };
@@ -41,8 +42,10 @@ impl Job {
 
         for (pipeline, end) in self.pipelines.iter_mut().zip(self.pipeline_ends.iter()) {
 
-            core.suspend_e_option = susp_e_option || end == "&&" || end == "||";
+            core.suspend_e_option = susp_e_option || end == "&&" || end == "||"
+                                    || pipeline.exclamation;
             if do_next {
+                core.judged_inside = false;
                 core.jobtable_check_status()?;
                 let (pids, exclamation, time, err) = pipeline.exec(core, pgid);
                 let waitstatuses = proc_ctrl::wait_pipeline(core, pids.clone(), exclamation, time);
@@ -56,7 +59,9 @@ impl Job {
 
             do_next = (core.db.exit_status == 0) == (end == "&&");
             signal::check_trap(core);
+            signal::notify(core);
         }
+        core.suspend_e_option = susp_e_option;
         signal::check_trap(core);
         Ok(())
     }
@@ -129,6 +134,9 @@ impl Job {
             //vec![self.exec_fork_bg(core, pgid)]
         };
         eprintln!("{}", &pids[0].unwrap().as_raw());
+        if let Some(Some(pid)) = pids.last() {
+            let _ = core.db.set_param("!", &pid.to_string(), Some(0));
+        }
         let len = pids.len();
         let new_job_id = core.generate_new_job_id();
         core.job_table_priority.insert(0, new_job_id);
@@ -18,7 +18,7 @@ pub struct Pipeline {
     pub commands: Vec<Box<dyn Command>>,
     pub pipes: Vec<Pipe>,
     pub text: String,
-    exclamation: bool,
+    pub exclamation: bool,
     pub time: bool,
 }
 
@@ -89,7 +89,7 @@ impl Pipeline {
     }
 
     fn eat_time(feeder: &mut Feeder, ans: &mut Self, core: &mut ShellCore) -> bool {
-        match feeder.starts_with("time") {
+        match feeder.starts_with("time") && feeder.scanner_name(core) == 4 {
             true  => ans.text += &feeder.consume(4),
             false => return false,
         }
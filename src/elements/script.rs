@@ -71,7 +71,7 @@ impl Script {
 
         if feeder.len() > 0 {
             let remaining = feeder.consume(feeder.len());
-            let first_token = remaining.split(" ").nth(0).unwrap().to_string();
+            let first_token = remaining.split([' ', '\n']).nth(0).unwrap().to_string();
             return Status::UnexpectedSymbol(first_token);
         }
 
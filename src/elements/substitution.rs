@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{ShellCore, Feeder};
+use crate::core::xtrace;
 use crate::error::parse::ParseError;
 use crate::error::exec::ExecError;
 use std::env;
@@ -34,7 +35,7 @@ impl Substitution {
             ParsedDataType::None 
             => self.evaluated_string = Some("".to_string()),
             ParsedDataType::Single(v) 
-            => if let Some(e) = self.eval_as_value(&v, core) {
+            => if let Some(e) = self.eval_as_value(&v, core, ! env) {
                 self.evaluated_string = Some(e);
             }
             ParsedDataType::Array(mut a) 
@@ -43,11 +44,23 @@ impl Substitution {
             }
         };
 
+        if ! env && ! matches!(self.value, ParsedDataType::Single(_)) {
+            core.xtrace(&self.text);
+        }
+
         match env {
             false => {
                 let ans = self.set_to_shell(core, layer);
                 if ! ans.is_ok() {
                     core.db.exit_status = 1;
+                    return ans;
+                }
+
+                if self.name == "PATH" {
+                    core.hash_clear(); // every assignment to PATH flushes the table
+                }
+                if core.db.flags.contains('a') && self.index.is_none() {
+                    let _ = self.set_to_env(); // allexport
                 }
                 ans
             },
@@ -147,16 +160,18 @@ impl Substitution {
         }
     }
 
-    fn eval_as_value(&self, w: &Word, core: &mut ShellCore) -> Option<String> {
+    fn eval_as_value(&self, w: &Word, core: &mut ShellCore, trace: bool) -> Option<String> {
         let prev = match self.append {
             true  => core.db.get_param(&self.name).unwrap_or(String::new()),
             false => "".to_string(),
         };
 
-        match w.eval_as_value(core) {
-            Some(s) => Some((prev + &s).to_string()),
-            None    => None,
+        let s = w.eval_as_value(core)?;
+        if trace { // name=, name[index]= or name+= and the value
+            let head = self.text.strip_suffix(&w.text).unwrap_or(&self.name);
+            core.xtrace(&(head.to_string() + &xtrace::quote(&s)));
         }
+        Some(prev + &s)
     }
 
     fn eval_as_array(&self, a: &mut Array, core: &mut ShellCore) -> Result<Vec<String>, String> {
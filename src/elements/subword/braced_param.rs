@@ -6,7 +6,7 @@ mod substr;
 mod remove;
 mod replace;
 
-use crate::{ShellCore, Feeder};
+use crate::{exit, ShellCore, Feeder};
 use crate::elements::subword;
 use crate::elements::subword::Subword;
 use crate::elements::subscript::Subscript;
@@ -67,6 +67,9 @@ impl Subword for BracedParam {
             }
         }
 
+        if self.unbound(core) {
+            return Err(exit::unbound_variable(&self.param.name, core));
+        }
         let value = core.db.get_param(&self.param.name).unwrap_or_default();
         self.text = match self.num {
             true  => value.chars().count().to_string(),
@@ -139,8 +142,25 @@ impl BracedParam {
         Ok(())
     }
 
+    /* set -u ignores ${name-word} and the like, and ${#name[@]} */
+    fn unbound(&self, core: &mut ShellCore) -> bool {
+        core.db.flags.contains('u') && self.value_check.is_none()
+        && core.db.is_unset(&self.param.name)
+    }
+
+    /* ${name[@]} and ${name[*]} are allowed for an unset array as bash 4.4 or later */
+    fn unbound_elem(&self, index: &str, core: &mut ShellCore) -> bool {
+        core.db.flags.contains('u') && self.value_check.is_none()
+        && index != "@" && index != "*"
+        && core.db.is_unset_elem(&self.param.name, index)
+    }
+
     fn subscript_operation(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
         let index = self.param.subscript.clone().unwrap().eval(core, &self.param.name)?;
+        if self.unbound_elem(&index, core) {
+            let name = format!("{}[{}]", &self.param.name, &index);
+            return Err(exit::unbound_variable(&name, core));
+        }
 
         if core.db.is_assoc(&self.param.name) {
             return self.subscript_operation_assoc(core, &index);
@@ -19,7 +19,7 @@ impl Remove {
         let mut text = text.clone();
         let pattern = self.remove_pattern.as_mut().unwrap()
                             .eval_for_case_word(core).ok_or(ExecError::Other("evaluation error".to_string()))?;
-        let extglob = core.shopts.query("extglob");
+        let extglob = core.options.query("extglob");
      
         if self.remove_symbol.starts_with("##") {
             let pat = glob::parse(&pattern, extglob);
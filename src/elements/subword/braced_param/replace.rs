@@ -36,7 +36,7 @@ impl Replace {
     pub fn get_text(&self, text: &String, core: &mut ShellCore) -> Result<String, ExecError> {
         let pattern = self.to_string(&self.replace_from, core)?;
         let string_to = self.to_string(&self.replace_to, core)?;
-        let extglob = core.shopts.query("extglob");
+        let extglob = core.options.query("extglob");
     
         let mut start = 0;
         let mut ans = String::new();
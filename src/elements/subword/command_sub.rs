@@ -28,9 +28,23 @@ impl Subword for CommandSubstitution {
     fn substitute(&mut self, core: &mut ShellCore) -> Result<(), ExecError> {
         let mut pipe = Pipe::new("|".to_string());
         pipe.set(-1, unistd::getpgrp());
-        let pid = self.command.exec(core, &mut pipe)?;
+        /* set -e is cleared in the subshell unless inherit_errexit is set */
+        let errexit = core.options.query("errexit");
+        if ! core.options.query("inherit_errexit") {
+            core.set_option("errexit", false);
+        }
+        core.comsub_level += 1;
+        let pid = self.command.exec(core, &mut pipe);
+        core.comsub_level -= 1;
+        core.set_option("errexit", errexit);
+        let pid = pid?;
         let result = self.read(pipe.recv, core);
+        /* the failure is reported by the command using the substitution */
+        let susp_e_option = core.suspend_e_option;
+        core.suspend_e_option = true;
         proc_ctrl::wait_pipeline(core, vec![pid], false, false);
+        core.suspend_e_option = susp_e_option;
+        core.command_substituted = true;
         result
     }
 }
@@ -121,7 +121,7 @@ impl ExtGlob {
     }
 
     pub fn parse(feeder: &mut Feeder, core: &mut ShellCore) -> Result<Option<Self>, ParseError> {
-        if ! core.shopts.query("extglob") 
+        if ! core.options.query("extglob") 
         || feeder.scanner_extglob_head() == 0 {
             return Ok(None);
         }
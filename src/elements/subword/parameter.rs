@@ -1,7 +1,7 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-use crate::{ShellCore, Feeder};
+use crate::{exit, ShellCore, Feeder};
 use crate::error::exec::ExecError;
 use super::Subword;
 
@@ -18,7 +18,11 @@ impl Subword for Parameter {
         if ! self.text.starts_with("$") {
             return Ok(());
         }
-        let value = core.db.get_param(&self.text[1..]).unwrap_or(String::new());
+        let name = &self.text[1..];
+        if core.db.flags.contains('u') && core.db.is_unset(name) {
+            return Err(exit::unbound_variable(name, core));
+        }
+        let value = core.db.get_param(name).unwrap_or(String::new());
         self.text = value.to_string();
         Ok(())
     }
@@ -116,10 +116,10 @@ impl Word {
 
     pub fn split_and_path_expansion(&self, core: &mut ShellCore) -> Vec<Word> {
         let mut ans = vec![];
-        let extglob = core.shopts.query("extglob");
+        let extglob = core.options.query("extglob");
 
         let splitted = split::eval(self, core);
-        if core.options.query("noglob") {
+        if core.db.flags.contains('f') {
             return splitted;
         }
 
@@ -42,12 +42,34 @@ fn get_value(text: &str, core: &mut ShellCore) -> Result<String, ExecError> {
         "" => "HOME",
         "+" => "PWD",
         "-" => "OLDPWD",
-        _ => return Ok(get_home_dir(text)),
+        _ => match get_dir_stack_elem(text, core) {
+            Some(dir) => return Ok(dir),
+            None      => return Ok(get_home_dir(text)),
+        },
     };
 
     core.db.get_param(key)
 }
 
+/* ~N, ~+N and ~-N refer to the directory stack as dirs +N or -N does */
+fn get_dir_stack_elem(text: &str, core: &mut ShellCore) -> Option<String> {
+    let (from_right, num) = match text.strip_prefix('-') {
+        Some(num) => (true, num),
+        None      => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    if num.is_empty() || ! num.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let n = num.parse::<usize>().ok()?;
+    let mut stack = core.get_dir_stack();
+    if from_right {
+        stack.reverse();
+    }
+    stack.get(n).cloned()
+}
+
 fn get_home_dir(user: &str) -> String {
     match User::from_name(user) {
         Ok(Some(u)) => u.dir
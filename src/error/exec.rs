@@ -30,6 +30,7 @@ pub enum ExecError {
     SyntaxError(String),
     Recursion(String),
     SubstringMinus(i64),
+    UnboundVariable(String),
     UnsupportedWaitStatus(WaitStatus),
     Errno(Errno),
     Other(String),
@@ -70,6 +71,7 @@ impl From<&ExecError> for String {
             ExecError::SyntaxError(near) => format!("syntax error near {}", &near),
             ExecError::Recursion(token) => format!("{0}: expression recursion level exceeded (error token is \"{0}\")", token), 
             ExecError::SubstringMinus(n) => format!("{}: substring expression < 0", n),
+            ExecError::UnboundVariable(name) => format!("{}: unbound variable", name),
             ExecError::UnsupportedWaitStatus(ws) => format!("Unsupported wait status: {:?}", ws),
             ExecError::Errno(e) => format!("system error {:?}", e),
             ExecError::Bug(msg) => format!("INTERNAL BUG: {}", msg),
@@ -1,13 +1,15 @@
 //SPDX-FileCopyrightText: 2022 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
-mod terminal;
+pub mod terminal;
 mod scanner;
 
-use std::{io, process};
+use std::process;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Lines};
+use std::vec::IntoIter;
 use crate::ShellCore;
+use crate::utils::fd_reader::FdReader;
 use crate::error::input::InputError;
 use crate::error::parse::ParseError;
 use std::sync::atomic::Ordering::Relaxed;
@@ -19,6 +21,7 @@ pub struct Feeder {
     pub nest: Vec<(String, Vec<String>)>,
     pub lineno: usize,
     script_lines: Option<Lines<BufReader<File>>>,
+    string_lines: Option<IntoIter<String>>,
 }
 
 impl Feeder {
@@ -36,6 +39,16 @@ impl Feeder {
         self.script_lines = Some(BufReader::new(file).lines());
     }
 
+    /* a string of eval or -c is fed line by line like a script */
+    pub fn set_string(&mut self, s: &str) {
+        let lines = s.split_inclusive('\n').map(|ln| ln.to_string()).collect::<Vec<String>>();
+        self.string_lines = Some(lines.into_iter());
+    }
+
+    fn reads_terminal(&self, core: &ShellCore) -> bool {
+        ! core.read_stdin && self.script_lines.is_none() && self.string_lines.is_none()
+    }
+
     pub fn consume(&mut self, cutpos: usize) -> String {
         let cut = self.remaining[0..cutpos].to_string();
         self.remaining = self.remaining[cutpos..].to_string();
@@ -72,6 +85,10 @@ impl Feeder {
     }   
 
     fn read_script(&mut self, core: &mut ShellCore) -> Result<String, InputError> {
+        if let Some(lines) = self.string_lines.as_mut() {
+            return lines.next().ok_or(InputError::Eof);
+        }
+
         if let Some(lines) = self.script_lines.as_mut() {
             match lines.next() {
                 Some(Ok(line)) => return Ok(line + "\n"),
@@ -79,18 +96,13 @@ impl Feeder {
             }
         }
 
-        let mut line = String::new();
-        let len = match io::stdin().read_line(&mut line) {
-            Ok(len)  => len,
+        match FdReader::new(0).line(b'\n') {
+            Ok(Some(line)) => Ok(String::from_utf8_lossy(&line).to_string()),
+            Ok(None) => Err(InputError::Eof),
             Err(why) => {
                 eprintln!("sush: {}: {}", &core.script_name, why);
                 process::exit(1)
             },
-        };
-
-        match len  {
-            0 => Err(InputError::Eof),
-            _ => Ok(line),
         }
     }
 
@@ -99,7 +111,7 @@ impl Feeder {
             return Err(InputError::Interrupt);
         }
 
-        let line = match ! core.read_stdin && self.script_lines.is_none() {
+        let line = match self.reads_terminal(core) {
             true  => terminal::read_line(core, "PS2"),
             false => self.read_script(core),
         };
@@ -125,7 +137,7 @@ impl Feeder {
     }
 
     pub fn feed_line(&mut self, core: &mut ShellCore) -> Result<(), InputError> {
-        let line = match ! core.read_stdin && self.script_lines.is_none() {
+        let line = match self.reads_terminal(core) {
             true  => terminal::read_line(core, "PS1"),
             false => self.read_script(core),
         };
@@ -135,7 +147,10 @@ impl Feeder {
 
     pub fn add_line(&mut self, line: String, core: &mut ShellCore) {
         if core.db.flags.contains('v') {
-            eprint!("{}", &line);
+            match line.ends_with('\n') {
+                true  => eprint!("{}", &line),
+                false => eprintln!("{}", &line),
+            }
         }
 
         match self.remaining.len() {
@@ -320,7 +320,7 @@ impl Feeder {
 
     pub fn scanner_redirect_symbol(&mut self, core: &mut ShellCore) -> usize {
         self.backslash_check_and_feed(vec!["<<", ">", "&", "<"], core);
-        self.scanner_one_of(&["<<<", "&>", ">&", ">>", "<", ">"])
+        self.scanner_one_of(&["<<<", "&>", ">&", ">>", ">|", "<", ">"])
     }
 
     pub fn scanner_parameter_alternative_symbol(&mut self) -> usize {
@@ -4,14 +4,16 @@
 mod completion;
 mod key;
 
-use crate::{file_check, ShellCore};
+use crate::{file_check, signal, ShellCore};
 use crate::utils::file;
 use crate::error::input::InputError;
 use std::io;
 use std::fs::File;
-use std::io::{Write, Stdout};
+use std::io::{Read, Write, Stdout};
 use std::sync::atomic::Ordering::Relaxed;
 use std::path::Path;
+use std::os::fd::RawFd;
+use nix::errno::Errno;
 use nix::unistd;
 use nix::unistd::User;
 use termion::event;
@@ -21,6 +23,25 @@ use termion::raw::{IntoRawMode, RawTerminal};
 use termion::input::TermRead;
 use unicode_width::UnicodeWidthChar;
 
+/* reads the terminal without buffering, giving an error when a signal arrives */
+struct TtyInput {
+    signal_pipe: Option<RawFd>,
+}
+
+impl Read for TtyInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if ! signal::wait_input(0, self.signal_pipe) {
+            return Err(io::Error::other("signal"));
+        }
+        loop {
+            match unistd::read(0, buf) {
+                Err(Errno::EINTR) => continue,
+                res => return res.map_err(io::Error::from),
+            }
+        }
+    }
+}
+
 struct Terminal {
     prompt: String,
     stdout: RawTerminal<Stdout>,
@@ -88,6 +109,10 @@ impl Terminal {
         let ansi_on_prompt = oct_to_hex_in_str(&raw_prompt);
 
         let replaced_prompt = Self::make_prompt_string(&ansi_on_prompt);
+        Self::new_with_prompt(&replaced_prompt)
+    }
+
+    fn new_with_prompt(replaced_prompt: &str) -> Self {
         let prompt = replaced_prompt.replace("\\[", "").replace("\\]", "").to_string();
         print!("{}", prompt);
         io::stdout().flush().unwrap();
@@ -103,7 +128,7 @@ impl Terminal {
             head: prompt.chars().count(),
             hist_ptr: 0,
             size: Terminal::size(),
-            prompt_width_map: Self::make_width_map(&replaced_prompt),
+            prompt_width_map: Self::make_width_map(replaced_prompt),
             prev_key: event::Key::Char('a'),
             tab_num: 0,
             completion_candidate: String::new(),
@@ -318,6 +343,19 @@ impl Terminal {
         }
     }
 
+    /* prints lines above the prompt and redraws the input under them */
+    pub fn print_above(&mut self, lines: &[String]) {
+        self.goto(self.chars.len());
+        self.write("\r\n");
+        for line in lines {
+            self.write(&format!("{}\r\n", line));
+        }
+        self.flush();
+        self.prompt_row = self.stdout.cursor_pos().unwrap_or((1, self.size.1 as u16)).1 as usize;
+        self.rewrite(true);
+        self.check_scroll();
+    }
+
     pub fn check_terminal_size(&mut self/*, prev_size: &mut (usize, usize)*/) {
         //if *prev_size == Terminal::size() {
         if self.size == Terminal::size() {
@@ -368,27 +406,58 @@ impl Terminal {
 
 fn signal_check(core: &mut ShellCore, term: &mut Terminal) -> Result<bool, InputError> {
     if core.sigint.load(Relaxed) 
-    || core.trapped.iter_mut().any(|t| t.0.load(Relaxed)) {
+    || signal::trap_pending(core) {
         term.write("\r\n");
         return Err(InputError::Interrupt);
     }
+
+    if core.db.flags.contains('b') {
+        let lines = core.jobtable_status_change();
+        if ! lines.is_empty() {
+            term.print_above(&lines);
+        }
+    }
     Ok(true)
 }
 
 pub fn read_line(core: &mut ShellCore, prompt: &str) -> Result<String, InputError>{
-    let mut term = Terminal::new(core, prompt);
+    let term = Terminal::new(core, prompt);
+    edit_line(core, term)
+}
+
+/* for the read builtin: the prompt is used literally and no history is left */
+pub fn read_line_with_initial(core: &mut ShellCore, prompt: &str, initial: &str)
+                              -> Result<String, InputError>{
+    let mut term = Terminal::new_with_prompt(prompt);
+    initial.chars().for_each(|c| term.insert(c));
+    let ans = edit_line(core, term);
+    if ans.is_ok() {
+        core.history.remove(0);
+    }
+    ans
+}
+
+fn edit_line(core: &mut ShellCore, mut term: Terminal) -> Result<String, InputError>{
     signal_check(core, &mut term)?;
 
     core.history.insert(0, String::new());
 
-    for c in io::stdin().keys() {
-        let c = c.unwrap();
-
+    let input = TtyInput { signal_pipe: signal::pipe_fd(core) };
+    for c in input.keys() {
         if let Err(e) = signal_check(core, &mut term) {
             core.history.remove(0);
             return Err(e);
         }
 
+        let c = match c {
+            Ok(c) => c,
+            Err(e) if e.kind() == io::ErrorKind::Other => continue, // woken up by a signal
+            Err(_) => {
+                core.history.remove(0);
+                return Err(InputError::Eof);
+            },
+        };
+
         term.check_terminal_size();
         match key::action(core, &mut term, &c) {
             Ok(true) => break,
@@ -131,6 +131,7 @@ impl Terminal {
                 let mut cands = match action.as_ref() {
                     "alias" => completion::compgen_a(core, args),
                     "command" => completion::compgen_c(core, args),
+                    "helptopic" => completion::compgen_helptopic(core, args),
                     "job" => completion::compgen_j(core, args),
                     "setopt" => completion::compgen_o(core, args),
                     "stopped" => completion::compgen_stopped(core, args),
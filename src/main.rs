@@ -56,7 +56,9 @@ fn configure(args: &Vec<String>) -> ShellCore {
     let mut options = vec![];
 
     for i in 1..args.len() {
-        if args[i].starts_with("-") {
+        if args[i] == "--verbose" {
+            options.push("-v".to_string());
+        }else if args[i].starts_with("-") {
             options.push(args[i].clone());
         }else{
             core.script_name = args[i].clone();
@@ -91,7 +93,7 @@ fn main() {
     }
 
     let mut core = configure(&args);
-    signal::run_signal_check(&mut core);
+    signal::init(&mut core);
 
     if core.script_name == "-" {
         read_rc_file(&mut core);
@@ -117,17 +119,27 @@ fn show_message() {
     eprintln!("Rusty Bash (a.k.a. Sushi shell), version {} - {}", V, P);
 }
 
+/* set -n is ignored by interactive shells */
+fn noexec(core: &ShellCore) -> bool {
+    core.db.flags.contains('n') && ! core.db.flags.contains('i')
+}
+
 fn main_loop(core: &mut ShellCore) {
     let mut feeder = Feeder::new("");
 
     if core.script_name != "-" {
         core.db.flags.retain(|f| f != 'i');
         feeder.set_file(&core.script_name);
+        let script = core.script_name.clone();
+        core.init_call_stack(&script);
     }
 
     if core.db.flags.contains('i') {
+        signal::init_interactive();
         show_message();
+        core.set_flag_option('m', true);
     }
+    option::import_shellopts(core);
 
     loop {
         if let Err(e) = core.jobtable_check_status() {
@@ -149,8 +161,13 @@ fn main_loop(core: &mut ShellCore) {
         core.sigint.store(false, Relaxed);
         match Script::parse(&mut feeder, core, false){
             Ok(Some(mut s)) => {
-                let _ = s.exec(core);
+                if ! noexec(core) {
+                    let _ = s.exec(core);
+                }
                 set_history(core, &s.get_text());
+                if core.db.flags.contains('t') {
+                    break;
+                }
             },
             Err(e) => {
                 e.print(core);
@@ -192,23 +209,28 @@ fn run_and_exit_c_option(args: &Vec<String>, c_parts: &Vec<String>) {
         exit::normal(&mut core);
     }
 
-    signal::run_signal_check(&mut core);
+    signal::init(&mut core);
     core.db.flags.retain(|f| f != 'i');
 
     core.db.flags += "c";
-    if core.db.flags.contains('v') {
-        eprintln!("{}", &c_parts[1]);
-    }
+    option::import_shellopts(&mut core);
 
-    let mut feeder = Feeder::new(&c_parts[1]);
-    match Script::parse(&mut feeder, &mut core, false){
-        Ok(Some(mut s)) => {
-            if let Err(e) = s.exec(&mut core) {
+    let mut feeder = Feeder::new("");
+    feeder.set_string(&c_parts[1]);
+    while feeder.feed_line(&mut core).is_ok() {
+        match Script::parse(&mut feeder, &mut core, false){
+            Ok(Some(_)) if noexec(&core) => {},
+            Ok(Some(mut s)) => {
+                if let Err(e) = s.exec(&mut core) {
+                    e.print(&mut core);
+                }
+            },
+            Err(e) => {
                 e.print(&mut core);
-            }
-        },
-        Err(e) => e.print(&mut core),
-        _ => {},
+                break;
+            },
+            _ => {},
+        }
     }
     exit::normal(&mut core)
 }
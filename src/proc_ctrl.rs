@@ -25,7 +25,7 @@ pub fn wait_pipeline(core: &mut ShellCore, pids: Vec<Option<Pid>>,
         if exclamation {
             core.flip_exit_status();
         }
-        exit::check_e_option(core);
+        check_failure(core, exclamation);
         return vec![];
     }
 
@@ -56,18 +56,29 @@ pub fn wait_pipeline(core: &mut ShellCore, pids: Vec<Option<Pid>>,
         core.flip_exit_status();
     }
 
-    exit::check_e_option(core);
-
+    check_failure(core, exclamation);
     ans
 }
 
+/* the ERR trap and set -e ignore a status that a compound command takes over from inside */
+fn check_failure(core: &mut ShellCore, exclamation: bool) {
+    if core.judged_inside || exclamation {
+        return;
+    }
+    signal::check_err_trap(core);
+    exit::check_e_option(core);
+}
+
 fn wait_process(core: &mut ShellCore, child: Pid) -> WaitStatus {
     let waitflags = match core.is_subshell {
         true  => None,
         false => Some(WaitPidFlag::WUNTRACED | WaitPidFlag::WCONTINUED)
     };
 
-    let ws = wait::waitpid(child, waitflags);
+    let mut ws = wait::waitpid(child, waitflags);
+    while ws == Err(Errno::EINTR) { // signals are handled after the command
+        ws = wait::waitpid(child, waitflags);
+    }
 
     core.db.exit_status = match ws {
         Ok(WaitStatus::Exited(_pid, status)) => status,
@@ -135,13 +146,21 @@ fn show_time(core: &ShellCore) {
                sys_diff.tv_sec()%60, sys_diff.tv_usec());
 }
 
-pub fn exec_command(args: &Vec<String>, core: &mut ShellCore) -> ! {
+pub fn exec_command(args: &Vec<String>, core: &mut ShellCore, hashed_path: &Option<String>) -> ! {
     let cargs = to_cargs(args);
 
-    match unistd::execvp(&cargs[0], &cargs) {
+    let result = match hashed_path {
+        Some(path) => unistd::execv(&CString::new(path.as_str()).unwrap(), &cargs),
+        None       => unistd::execvp(&cargs[0], &cargs),
+    };
+
+    match result {
         Err(Errno::E2BIG) => exit::arg_list_too_long(&args[0], core),
         Err(Errno::EACCES) => exit::permission_denied(&args[0], core),
-        Err(Errno::ENOENT) => run_command_not_found(&args[0], core),
+        Err(Errno::ENOENT) => match hashed_path {
+            Some(path) => exit::no_such_file(path, core),
+            None       => run_command_not_found(&args[0], core),
+        },
         Err(err) => {
             eprintln!("Failed to execute. {:?}", err);
             process::exit(127)
@@ -1,16 +1,19 @@
 //SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
 //SPDX-License-Identifier: BSD-3-Clause
 
+use nix::{fcntl, libc, unistd};
+use nix::errno::Errno;
+use nix::poll::{PollFd, PollFlags, PollTimeout};
 use nix::sys::signal;
 use nix::sys::signal::{Signal, SigHandler};
-use std::{thread, time};
-use std::sync::Arc;
+use std::mem;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicI32};
 use std::sync::atomic::Ordering::Relaxed;
-use crate::Script;
+use crate::{exit, Script};
 use crate::core::ShellCore;
 use crate::feeder::Feeder;
-use signal_hook::consts;
-use signal_hook::iterator::Signals;
 
 pub fn ignore(sig: Signal) {
     unsafe { signal::signal(sig, SigHandler::SigIgn) }
@@ -22,32 +25,300 @@ pub fn restore(sig: Signal) {
         .expect("sush(fatal): cannot restore signal");
 }
 
-pub fn run_signal_check(core: &mut ShellCore) {
-    for fd in 3..10 { //use FD 3~9 to prevent signal-hool from using these FDs
-        nix::unistd::dup2(2, fd).expect("sush(fatal): init error");
+/* names of signals without the "SIG" prefix, including real-time ones */
+pub fn num_to_name(num: i32) -> Option<String> {
+    let (min, max) = (libc::SIGRTMIN(), libc::SIGRTMAX());
+    if num == min {
+        return Some("RTMIN".to_string());
+    }else if num == max {
+        return Some("RTMAX".to_string());
+    }else if num > min && num <= (min + max)/2 {
+        return Some(format!("RTMIN+{}", num - min));
+    }else if num > min && num < max {
+        return Some(format!("RTMAX-{}", max - num));
     }
 
-    let sigint = Arc::clone(&core.sigint);
- 
-    thread::spawn(move || {
-        let mut signals = Signals::new(vec![consts::SIGINT])
-                          .expect("sush(fatal): cannot prepare signal data");
+    let sig = Signal::try_from(num).ok()?;
+    Some(sig.as_str()[3..].to_string())
+}
+
+pub fn name_to_num(name: &str) -> Option<i32> {
+    if let Ok(n) = name.parse::<i32>() {
+        return match n >= 0 && n <= libc::SIGRTMAX() {
+            true  => Some(n),
+            false => None,
+        };
+    }
+
+    let upper = name.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    if let Ok(sig) = Signal::from_str(&("SIG".to_owned() + name)) {
+        return Some(sig as i32);
+    }
+
+    (libc::SIGRTMIN()..=libc::SIGRTMAX()).find(|n| num_to_name(*n).as_deref() == Some(name))
+}
+
+pub fn nums() -> Vec<i32> {
+    (1..=libc::SIGRTMAX()).filter(|n| num_to_name(*n).is_some()).collect()
+}
+
+pub fn print_signal_list() {
+    let nums = nums();
+    for (i, n) in nums.iter().enumerate() {
+        let sep = match (i+1).is_multiple_of(5) {
+            true  => "\n",
+            false => "\t",
+        };
+        print!("{:2}) SIG{}{}", n, num_to_name(*n).unwrap(), sep);
+    }
+    if ! nums.len().is_multiple_of(5) {
+        println!();
+    }
+}
 
-        for fd in 3..10 { // release FD 3~9
-            nix::unistd::close(fd).expect("sush(fatal): init error");
+/* flags set by the handler of trapped signals, indexed by the signal number */
+const NSIG: usize = 65;
+static TRAPPED: [AtomicBool; NSIG] = [const { AtomicBool::new(false) }; NSIG];
+
+/* pseudo signals for trap, numbered after the real ones */
+pub const DEBUG: i32 = NSIG as i32;
+pub const ERR: i32 = NSIG as i32 + 1;
+pub const RETURN: i32 = NSIG as i32 + 2;
+
+/* the write end of the self-pipe, which wakes up poll when a signal arrives */
+static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn catch(sig: libc::c_int) {
+    if let Some(flag) = TRAPPED.get(sig as usize) {
+        flag.store(true, Relaxed);
+    }
+
+    let fd = WAKE_FD.load(Relaxed);
+    if fd >= 0 {
+        let errno = Errno::last_raw();
+        let byte = sig as u8;
+        unsafe { libc::write(fd, &byte as *const u8 as *const libc::c_void, 1) };
+        Errno::set_raw(errno);
+    }
+}
+
+fn set_action(num: i32, action: &libc::sigaction) -> Option<libc::sigaction> {
+    let mut old: libc::sigaction = unsafe { mem::zeroed() };
+    match unsafe { libc::sigaction(num, action, &mut old) } {
+        0 => Some(old),
+        _ => None,
+    }
+}
+
+fn set_handler(num: i32, handler: libc::sighandler_t) -> Option<libc::sigaction> {
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = handler;
+    action.sa_flags = match num {
+        libc::SIGCHLD => libc::SA_RESTART, // only wakes up poll through the pipe
+        _ => 0, // no SA_RESTART so that waitpid and read can be interrupted
+    };
+    unsafe { libc::sigemptyset(&mut action.sa_mask) };
+    set_action(num, &action)
+}
+
+/* an empty command means that the signal is ignored */
+pub fn set_trap(core: &mut ShellCore, num: i32, command: &str) {
+    if num > 0 && num < DEBUG {
+        let handler = match command.is_empty() {
+            true  => libc::SIG_IGN,
+            false => catch as *const () as libc::sighandler_t,
+        };
+        if let Some(old) = set_handler(num, handler) {
+            core.trap_defaults.entry(num).or_insert(old);
         }
+    }
+    core.traps.insert(num, command.to_string());
+}
 
-        loop {
-            thread::sleep(time::Duration::from_millis(100)); //0.1秒周期に変更
-            for signal in signals.pending() {
-                if signal == consts::SIGINT {
-                    sigint.store(true, Relaxed);
-                    eprintln!("^C");
-                }
+pub fn reset_trap(core: &mut ShellCore, num: i32) {
+    if core.traps.remove(&num).is_none() || num == 0 || num >= DEBUG {
+        return;
+    }
+
+    match core.trap_defaults.remove(&num) {
+        Some(old) => { set_action(num, &old); },
+        None      => { set_handler(num, libc::SIG_DFL); },
+    }
+    if let Some(flag) = TRAPPED.get(num as usize) {
+        flag.store(false, Relaxed);
+    }
+}
+
+/* subshells keep ignored signals and reset the others,
+ * except ERR with errtrace and DEBUG/RETURN with functrace */
+pub fn reset_traps_in_subshell(core: &mut ShellCore) {
+    let traps = core.traps.clone();
+    core.parent_traps.extend(traps.clone());
+    core.trap_defaults.clear();
+    for (num, command) in traps {
+        let inherited = (num == ERR && core.db.flags.contains('E'))
+                        || ((num == DEBUG || num == RETURN) && core.db.flags.contains('T'));
+        match command.is_empty() {
+            _ if inherited => {},
+            true  => set_trap(core, num, ""),
+            false => reset_trap(core, num),
+        }
+    }
+    core.trap_defaults.clear();
+}
+
+/* moves the pipe to a high number so that it doesn't conflict with redirections */
+fn high_fd(fd: OwnedFd) -> OwnedFd {
+    match fcntl::fcntl(fd.as_raw_fd(), fcntl::F_DUPFD_CLOEXEC(256)) {
+        Ok(high) => unsafe { OwnedFd::from_raw_fd(high) },
+        Err(_)   => fd,
+    }
+}
+
+/* makes the self-pipe of this process; subshells call this again
+ * so that they don't share the pipe with the parent */
+fn init_pipe(core: &mut ShellCore) {
+    let (read, write) = match unistd::pipe() {
+        Ok((r, w)) => (high_fd(r), high_fd(w)),
+        Err(_) => return,
+    };
+    for fd in [&read, &write] {
+        let _ = fcntl::fcntl(fd.as_raw_fd(), fcntl::F_SETFL(fcntl::OFlag::O_NONBLOCK));
+    }
+
+    let old = WAKE_FD.swap(write.into_raw_fd(), Relaxed);
+    if old >= 0 {
+        let _ = unistd::close(old);
+    }
+    core.signal_pipe = Some(read);
+}
+
+pub fn init(core: &mut ShellCore) {
+    init_pipe(core);
+    set_handler(libc::SIGINT, catch as *const () as libc::sighandler_t);
+    set_handler(libc::SIGCHLD, catch as *const () as libc::sighandler_t);
+}
+
+/* an interactive shell ignores SIGTERM and passes SIGHUP to the jobs */
+pub fn init_interactive() {
+    set_handler(libc::SIGHUP, catch as *const () as libc::sighandler_t);
+    ignore(Signal::SIGTERM);
+}
+
+pub fn init_subshell(core: &mut ShellCore) {
+    restore(Signal::SIGHUP);
+    restore(Signal::SIGTERM);
+    restore(Signal::SIGCHLD);
+    init_pipe(core);
+}
+
+/* returns the signal whose handler has run and whose flag is not cleared yet;
+ * SIGCHLD is left out since it doesn't stop waiting */
+pub fn caught() -> Option<i32> {
+    (1..NSIG).filter(|n| *n != libc::SIGCHLD as usize)
+             .find(|n| TRAPPED[*n].load(Relaxed)).map(|n| n as i32)
+}
+
+fn drain(core: &ShellCore) {
+    if let Some(fd) = core.signal_pipe.as_ref() {
+        let mut buf = [0; 64];
+        while let Ok(n) = unistd::read(fd.as_raw_fd(), &mut buf) {
+            if n == 0 {
+                break;
             }
         }
-    });
-} //thanks: https://dev.to/talzvon/handling-unix-kill-signals-in-rust-55g6
+    }
+}
+
+/* the signal that has no trap is handled here */
+fn receive(core: &mut ShellCore) {
+    drain(core);
+
+    let no_trap = |core: &ShellCore, sig: i32| ! core.traps.contains_key(&sig);
+
+    if no_trap(core, libc::SIGINT) && TRAPPED[libc::SIGINT as usize].swap(false, Relaxed) {
+        core.sigint.store(true, Relaxed);
+        if core.db.flags.contains('i') {
+            eprintln!(); // after "^C" echoed by the terminal
+        }
+    }
+
+    if no_trap(core, libc::SIGHUP) && TRAPPED[libc::SIGHUP as usize].swap(false, Relaxed) {
+        hangup(core);
+    }
+
+    let chld = &TRAPPED[libc::SIGCHLD as usize];
+    let reap = match no_trap(core, libc::SIGCHLD) {
+        true  => chld.swap(false, Relaxed),
+        false => chld.load(Relaxed), // cleared when the trap runs
+    };
+    if reap {
+        let _ = core.jobtable_check_status();
+    }
+}
+
+/* sleeps until a signal arrives, including one that arrived after the last call */
+pub fn pause(core: &ShellCore) {
+    if let Some(fd) = core.signal_pipe.as_ref() {
+        let mut fds = [PollFd::new(fd.as_fd(), PollFlags::POLLIN)];
+        let _ = nix::poll::poll(&mut fds, PollTimeout::NONE);
+    }
+    drain(core);
+}
+
+/* reports finished jobs at once with set -b */
+pub fn notify(core: &mut ShellCore) {
+    if core.db.flags.contains('b') {
+        for line in core.jobtable_status_change() {
+            println!("{}", line);
+        }
+    }
+}
+
+fn hangup(core: &mut ShellCore) -> ! {
+    for job in core.job_table.iter_mut().filter(|j| ! j.no_hup) {
+        let _ = job.send_signal(libc::SIGHUP);
+    }
+    core.db.exit_status = 128 + libc::SIGHUP;
+    exit::normal(core)
+}
+
+pub fn trap_pending(core: &mut ShellCore) -> bool {
+    receive(core);
+    core.traps.iter().any(|(n, c)| ! c.is_empty()
+                          && TRAPPED.get(*n as usize).is_some_and(|f| f.load(Relaxed)))
+}
+
+pub fn pipe_fd(core: &ShellCore) -> Option<RawFd> {
+    core.signal_pipe.as_ref().map(|p| p.as_raw_fd())
+}
+
+/* waits until fd becomes readable; false means that a signal arrives first */
+pub fn wait_input(fd: RawFd, pipe: Option<RawFd>) -> bool {
+    let pipe = match pipe {
+        Some(p) => unsafe { BorrowedFd::borrow_raw(p) },
+        None    => return true,
+    };
+
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    loop {
+        let mut fds = [PollFd::new(fd, PollFlags::POLLIN), PollFd::new(pipe, PollFlags::POLLIN)];
+        match nix::poll::poll(&mut fds, PollTimeout::NONE) {
+            Ok(_) => {
+                let ready = |f: &PollFd| f.revents().is_some_and(|r| ! r.is_empty());
+                if ready(&fds[0]) {
+                    return true;
+                }
+                if ready(&fds[1]) {
+                    return false;
+                }
+            },
+            Err(Errno::EINTR) => {},
+            Err(_) => return true,
+        }
+    }
+}
 
 pub fn input_interrupt_check(feeder: &mut Feeder, core: &mut ShellCore) -> bool {
     if ! core.sigint.load(Relaxed) { //core.input_interrupt {
@@ -60,29 +331,85 @@ pub fn input_interrupt_check(feeder: &mut Feeder, core: &mut ShellCore) -> bool
     true
 }
 
+fn run_trap_script(core: &mut ShellCore, s: &str) {
+    let mut feeder = Feeder::new(s);
+    let mut script = match Script::parse(&mut feeder, core, true) {
+        Ok(Some(s)) => s,
+        Ok(None) => return,
+        Err(e) => {
+            e.print(core);
+            return;
+        },
+    };
+
+    if let Err(e) = script.exec(core) {
+        e.print(core);
+    }
+}
+
 pub fn check_trap(core: &mut ShellCore) {
+    receive(core);
     let bkup = core.db.exit_status;
 
     let mut scripts = vec![];
-    for t in &core.trapped {
-        if t.0.load(Relaxed) {
-            scripts.push(t.1.clone());
-            t.0.store(false, Relaxed);
+    for (n, command) in &core.traps {
+        if *n != 0 && ! command.is_empty()
+        && TRAPPED.get(*n as usize).is_some_and(|f| f.swap(false, Relaxed)) {
+            scripts.push(command.clone());
         }
     }
 
     for s in scripts {
-        let mut feeder = Feeder::new(&s);
-        let mut script = match Script::parse(&mut feeder, core, true) {
-            Ok(None) => {continue;},
-            Ok(s) => s.unwrap(),
-            Err(e) => {e.print(core); continue;},
-        };
+        run_trap_script(core, &s);
+    }
 
-        if let Err(e) = script.exec(core) {
-            e.print(core);
-        }
+    core.db.exit_status = bkup;
+}
+
+/* runs the trap of DEBUG, ERR or RETURN, which never nest */
+pub fn run_pseudo_trap(core: &mut ShellCore, num: i32) {
+    if core.running_trap {
+        return;
     }
+    let command = match core.traps.get(&num) {
+        Some(c) if ! c.is_empty() => c.clone(),
+        _ => return,
+    };
 
+    let bkup = core.db.exit_status;
+    core.running_trap = true;
+    run_trap_script(core, &command);
+    core.running_trap = false;
     core.db.exit_status = bkup;
 }
+
+pub fn check_err_trap(core: &mut ShellCore) {
+    if core.db.exit_status == 0 || core.suspend_e_option {
+        return;
+    }
+
+    if core.traps.contains_key(&ERR) {
+        run_pseudo_trap(core, ERR);
+    }
+}
+
+/* hides the traps that functions don't inherit without errtrace or functrace */
+pub fn enter_function(core: &mut ShellCore) -> Vec<(i32, String)> {
+    let mut hidden = vec![];
+    for (num, opt) in [(ERR, 'E'), (DEBUG, 'T'), (RETURN, 'T')] {
+        if ! core.db.flags.contains(opt) {
+            if let Some(c) = core.traps.remove(&num) {
+                hidden.push((num, c));
+            }
+        }
+    }
+    hidden
+}
+
+/* runs the RETURN trap and restores the hidden traps unless the function set new ones */
+pub fn leave_function(core: &mut ShellCore, hidden: Vec<(i32, String)>) {
+    run_pseudo_trap(core, RETURN);
+    for (num, command) in hidden {
+        core.traps.entry(num).or_insert(command);
+    }
+}
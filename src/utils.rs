@@ -4,6 +4,7 @@
 pub mod directory;
 pub mod clock;
 pub mod exit;
+pub mod fd_reader;
 pub mod file;
 pub mod file_check;
 pub mod glob;
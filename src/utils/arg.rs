@@ -51,6 +51,51 @@ pub fn dissolve_options(args: &Vec<String>) -> Vec<String> {
     args.iter().map(|a| dissolve_option(a)).collect::<Vec<Vec<String>>>().concat()
 }
 
+/* parses options like the getopt function of C. Each char followed by ':'
+ * in optstring takes an argument, which can be attached to the option. */
+pub fn getopt(args: &[String], optstring: &str)
+    -> Result<(Vec<(char, Option<String>)>, Vec<String>), String> {
+    let mut opts = vec![];
+    let mut pos = 0;
+
+    while pos < args.len() {
+        let arg = &args[pos];
+        if arg == "--" {
+            pos += 1;
+            break;
+        }
+        if ! arg.starts_with("-") || arg == "-" {
+            break;
+        }
+
+        for (i, c) in arg.char_indices().skip(1) {
+            let with_arg = match optstring.find(c) {
+                Some(n) if c != ':' => optstring[n+c.len_utf8()..].starts_with(":"),
+                _ => return Err(format!("-{}: invalid option", c)),
+            };
+
+            if ! with_arg {
+                opts.push((c, None));
+                continue;
+            }
+
+            let rest = &arg[i+c.len_utf8()..];
+            if ! rest.is_empty() {
+                opts.push((c, Some(rest.to_string())));
+            }else if pos+1 < args.len() {
+                pos += 1;
+                opts.push((c, Some(args[pos].clone())));
+            }else{
+                return Err(format!("-{}: option requires an argument", c));
+            }
+            break;
+        }
+        pos += 1;
+    }
+
+    Ok((opts, args[pos..].to_vec()))
+}
+
 pub fn consume_after_options(args: &mut Vec<String>, start: usize) -> Vec<String> {
     let mut has_option = false;
 
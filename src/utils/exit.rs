@@ -2,6 +2,7 @@
 //SPDX-License-Identifier: BSD-3-Clause
 
 use crate::{error, Feeder, Script, ShellCore};
+use crate::error::exec::ExecError;
 use std::process;
 
 pub fn normal(core: &mut ShellCore) -> ! {
@@ -12,11 +13,13 @@ pub fn normal(core: &mut ShellCore) -> ! {
 }
 
 fn run_script(core: &mut ShellCore) {
-    if core.exit_script.is_empty() {
-        return;
-    }
+    let script = match core.traps.remove(&0) {
+        Some(s) if ! s.is_empty() => s,
+        _ => return,
+    };
 
-    let mut feeder = Feeder::new(&core.exit_script);
+    let exit_status = core.db.exit_status; // kept unless the trap calls exit
+    let mut feeder = Feeder::new(&script);
     match Script::parse(&mut feeder, core, true) {
         Ok(Some(mut s)) => {
             if let Err(e) = s.exec(core) {
@@ -26,6 +29,7 @@ fn run_script(core: &mut ShellCore) {
         Err(e) => {e.print(core);},
         Ok(None) => {},
     };
+    core.db.exit_status = exit_status;
 }
 
 /* error at exec */
@@ -43,6 +47,10 @@ pub fn permission_denied(command_name: &str, core: &mut ShellCore) -> ! {
     command_error_exit(command_name, core, "Permission denied", 126)
 }
 
+pub fn no_such_file(path: &str, core: &mut ShellCore) -> ! {
+    command_error_exit(path, core, "No such file or directory", 127)
+}
+
 pub fn not_found(command_name: &str, core: &mut ShellCore) -> ! {
     command_error_exit(command_name, core, "command not found", 127)
 }
@@ -58,3 +66,17 @@ pub fn check_e_option(core: &mut ShellCore) {
         normal(core);
     }
 }
+
+/* set -u: an unbound variable aborts a non-interactive shell or a subshell */
+pub fn unbound_variable(name: &str, core: &mut ShellCore) -> ExecError {
+    let e = match name.parse::<usize>().is_ok() || name == "!" {
+        true  => ExecError::UnboundVariable(format!("${}", name)),
+        false => ExecError::UnboundVariable(name.to_string()),
+    };
+    if core.is_subshell || ! core.db.flags.contains('i') {
+        e.print(core);
+        core.db.exit_status = if core.is_subshell { 1 } else { 127 };
+        normal(core);
+    }
+    e
+}
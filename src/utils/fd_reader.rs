@@ -0,0 +1,182 @@
+//SPDX-FileCopyrightText: 2024 Ryuichi Ueda <ryuichiueda@gmail.com>
+//SPDX-License-Identifier: BSD-3-Clause
+
+use crate::signal;
+use nix::errno::Errno;
+use nix::poll::{PollFd, PollFlags, PollTimeout};
+use nix::unistd;
+use nix::unistd::Whence;
+use std::os::fd::{BorrowedFd, RawFd};
+use std::time::Instant;
+
+#[derive(Debug)]
+pub enum ReadError {
+    Timeout,
+    Signal(i32),
+    Errno(Errno),
+}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReadError::Timeout     => write!(f, "timed out"),
+            ReadError::Signal(sig) => write!(f, "interrupted by signal {}", sig),
+            ReadError::Errno(e)    => write!(f, "{}", e.desc()),
+        }
+    }
+}
+
+/* A reader that never takes more bytes from a file descriptor than it hands out.
+ * Pipes and terminals are read byte by byte, and seekable files are read in
+ * chunks whose unused part is given back with lseek when the reader is dropped. */
+#[derive(Debug)]
+pub struct FdReader {
+    fd: RawFd,
+    seekable: bool,
+    buf: Vec<u8>,
+    pos: usize,
+    pub deadline: Option<Instant>,
+    pub interruptible: bool, // a caught signal stops reading
+}
+
+impl Drop for FdReader {
+    fn drop(&mut self) {
+        if self.seekable && self.pos < self.buf.len() {
+            let rest = (self.buf.len() - self.pos) as i64;
+            let _ = unistd::lseek(self.fd, -rest, Whence::SeekCur);
+        }
+    }
+}
+
+impl FdReader {
+    pub fn new(fd: RawFd) -> FdReader {
+        FdReader {
+            fd,
+            seekable: unistd::lseek(fd, 0, Whence::SeekCur).is_ok(),
+            buf: vec![],
+            pos: 0,
+            deadline: None,
+            interruptible: false,
+        }
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> FdReader {
+        FdReader {
+            fd: -1,
+            seekable: false,
+            buf: bytes,
+            pos: 0,
+            deadline: None,
+            interruptible: false,
+        }
+    }
+
+    fn check_signal(&self) -> Result<(), ReadError> {
+        match signal::caught() {
+            Some(sig) if self.interruptible => Err(ReadError::Signal(sig)),
+            _ => Ok(()),
+        }
+    }
+
+    fn wait(&self) -> Result<(), ReadError> {
+        let deadline = match self.deadline {
+            Some(d) => d,
+            None    => return Ok(()),
+        };
+
+        let fd = unsafe { BorrowedFd::borrow_raw(self.fd) };
+        let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let timeout = PollTimeout::try_from(remaining).unwrap_or(PollTimeout::MAX);
+            match nix::poll::poll(&mut fds, timeout) {
+                Ok(0) => return Err(ReadError::Timeout),
+                Ok(_) => return Ok(()),
+                Err(Errno::EINTR) => self.check_signal()?,
+                Err(e) => return Err(ReadError::Errno(e)),
+            }
+        }
+    }
+
+    pub fn byte(&mut self) -> Result<Option<u8>, ReadError> {
+        if self.pos < self.buf.len() {
+            self.pos += 1;
+            return Ok(Some(self.buf[self.pos-1]));
+        }
+
+        if self.fd < 0 {
+            return Ok(None);
+        }
+
+        self.wait()?;
+        self.buf.resize(if self.seekable { 128 } else { 1 }, 0);
+        self.pos = 0;
+        loop {
+            match unistd::read(self.fd, &mut self.buf) {
+                Ok(0) => {
+                    self.buf.clear();
+                    return Ok(None);
+                },
+                Ok(n) => {
+                    self.buf.truncate(n);
+                    self.pos = 1;
+                    return Ok(Some(self.buf[0]));
+                },
+                Err(Errno::EINTR) => self.check_signal()?,
+                Err(e) => {
+                    self.buf.clear();
+                    return Err(ReadError::Errno(e));
+                },
+            }
+        }
+    }
+
+    /* reads the bytes of one UTF-8 character */
+    pub fn char(&mut self) -> Result<Option<Vec<u8>>, ReadError> {
+        let first = match self.byte()? {
+            Some(b) => b,
+            None    => return Ok(None),
+        };
+
+        let len = match first {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _           => 1,
+        };
+
+        let mut ans = vec![first];
+        while ans.len() < len {
+            match self.byte() {
+                Ok(Some(b)) if b & 0xC0 == 0x80 => ans.push(b),
+                Ok(Some(_)) => {
+                    self.pos -= 1;
+                    break;
+                },
+                _ => break,
+            }
+        }
+        Ok(Some(ans))
+    }
+
+    /* reads bytes until the delimiter, which is included in the answer */
+    pub fn line(&mut self, delim: u8) -> Result<Option<Vec<u8>>, ReadError> {
+        let mut ans = vec![];
+        loop {
+            match self.byte()? {
+                Some(b) => {
+                    ans.push(b);
+                    if b == delim {
+                        return Ok(Some(ans));
+                    }
+                },
+                None => {
+                    return match ans.is_empty() {
+                        true  => Ok(None),
+                        false => Ok(Some(ans)),
+                    };
+                },
+            }
+        }
+    }
+}
@@ -4,7 +4,6 @@
 
 use crate::ShellCore;
 use crate::utils::file_check;
-use std::env;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf, Component};
 
@@ -16,20 +15,21 @@ pub fn buf_to_name(path: &PathBuf) -> String {
     path.to_string_lossy().to_string()
 }
 
-pub fn search_command(command: &str) -> Option<String> {
-    let paths = env::var_os("PATH");
-    if paths.is_none() {
-        return None;
-    }
+fn executables<'a>(command: &'a str, paths: &'a str) -> impl Iterator<Item = String> + 'a {
+    paths.split(":").map(move |dir| {
+        let dir = if dir.is_empty() { "." } else { dir };
+        dir.to_owned() + "/" + command
+    }).filter(|compath| file_check::is_executable(compath) && ! file_check::is_dir(compath))
+}
 
-    for path in env::split_paths(&paths.unwrap()) {
-        let compath = buf_to_name(&path) + "/" + command;
-        if file_check::exists(&compath) {
-            return Some(compath);
-        }
-    }
+/* stops at the first directory that has the command */
+pub fn search_command(command: &str, paths: &str) -> Option<String> {
+    executables(command, paths).next()
+}
 
-    None
+/* lists executable files in the directories of paths in order */
+pub fn search_command_all(command: &str, paths: &str) -> Vec<String> {
+    executables(command, paths).collect()
 }
 
 pub fn make_absolute_path(core: &mut ShellCore, path_str: &str) -> PathBuf {
@@ -7,7 +7,7 @@ err () {
 	exit 1
 }
 
-cargo build --release || err $LINENO
+cargo build --release --workspace || err $LINENO
 cargo --version
 
 cd $(dirname $0)
@@ -25,8 +25,10 @@ com=../target/release/sush
 ./test_brace.bash nobuild &
 ./test_builtins.bash nobuild &
 ./test_options.bash nobuild &
+./test_errexit.bash nobuild &
 ./test_parameters.bash nobuild &
 ./test_glob.bash nobuild &
+./test_plugin.bash nobuild &
 
 wait 
 
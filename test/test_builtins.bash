@@ -23,8 +23,48 @@ res=$($com <<< 'rm -f /tmp/link; cd /tmp; mkdir -p hoge; ln -s hoge link; cd lin
 /private/tmp/hoge" ] || err $LINENO
 
 res=$($com <<< 'pwd -a 2>/tmp/rusty_bash; cat /tmp/rusty_bash')
-[ "$res" = "sush: pwd: -a: invalid option
-pwd: usage: pwd [-LP]" ] || err $LINENO
+[[ "$res" =~ ": line 1: pwd: -a: invalid option
+pwd: usage: pwd [-LP]"$ ]] || err $LINENO
+
+res=$($com <<< 'rm -rf /tmp/rusty_bash_cd ; mkdir -p /tmp/rusty_bash_cd/a/b /tmp/rusty_bash_cd/real ; ln -s real /tmp/rusty_bash_cd/link')
+
+res=$($com <<< 'cd /tmp ; CDPATH=/tmp/rusty_bash_cd/a cd b ; pwd')
+[ "$res" = "/tmp/rusty_bash_cd/a/b
+/tmp/rusty_bash_cd/a/b" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/rusty_bash_cd ; CDPATH=:/tmp/rusty_bash_cd/a cd a ; pwd')
+[ "$res" = "/tmp/rusty_bash_cd/a" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp/rusty_bash_cd/link ; echo $PWD ; cd -P . ; echo $PWD')
+[ "$res" = "/tmp/rusty_bash_cd/link
+/tmp/rusty_bash_cd/real" ] || err $LINENO
+
+res=$($com <<< 'set -P ; cd /tmp/rusty_bash_cd/link ; pwd ; cd -L /tmp/rusty_bash_cd/link ; pwd')
+[ "$res" = "/tmp/rusty_bash_cd/real
+/tmp/rusty_bash_cd/link" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp ; cd / ; cd - ; echo $OLDPWD')
+[ "$res" = "/tmp
+/" ] || err $LINENO
+
+res=$($com <<< 'shopt -s cdable_vars ; v=/tmp/rusty_bash_cd/a ; cd v ; pwd')
+[ "$res" = "/tmp/rusty_bash_cd/a
+/tmp/rusty_bash_cd/a" ] || err $LINENO
+
+res=$($com <<< 'cd /nonexist 2>&1')
+[[ "$res" =~ "cd: /nonexist: No such file or directory"$ ]] || err $LINENO
+
+res=$($com <<< 'touch /tmp/rusty_bash_cd/file ; cd /tmp/rusty_bash_cd/file 2>&1')
+[[ "$res" =~ "cd: /tmp/rusty_bash_cd/file: Not a directory"$ ]] || err $LINENO
+
+res=$($com <<< 'cd a b')
+[ "$?" = "1" ] || err $LINENO
+
+res=$($com <<< 'cd -x')
+[ "$?" = "2" ] || err $LINENO
+
+res=$($com <<< 'unset HOME ; cd')
+[ "$?" = "1" ] || err $LINENO
 
 echo aaaaaaaaaaaaaaaa > /tmp/hoge.txt
 res=$($com <<< 'source /tmp/hoge.txt')
@@ -81,6 +121,59 @@ res=$($com <<< 'A () { echo aaa ; } ; unset -f A ; A')
 res=$($com <<< 'A () { echo aaa ; } ; unset A ; A')
 [ "$res" = "" ] || err $LINENO
 
+# pushd, popd, dirs
+
+res=$($com <<< 'cd /tmp ; pushd /usr ; pushd /etc ; dirs -v ; echo ${DIRSTACK[@]}')
+[ "$res" == "/usr /tmp
+/etc /usr /tmp
+ 0  /etc
+ 1  /usr
+ 2  /tmp
+/etc /usr /tmp" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp ; pushd /usr ; pushd /etc ; pushd ; pushd +2 ; popd ; pwd')
+[ "$res" == "/usr /tmp
+/etc /usr /tmp
+/usr /etc /tmp
+/tmp /usr /etc
+/usr /etc
+/usr" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp ; pushd -n /usr ; pushd -n /etc ; popd -n ; popd -0 ; pwd')
+[ "$res" == "/tmp /usr
+/tmp /etc /usr
+/tmp /usr
+/tmp
+/tmp" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp ; pushd -n /usr > /dev/null ; dirs +1 ; dirs -1 ; echo ~1 ~+0 ~-0 ~+5')
+[ "$res" == "/usr
+/tmp
+/usr /tmp /usr ~+5" ] || err $LINENO
+
+res=$($com <<< 'cd ; dirs ; dirs -l')
+[ "$res" == "~
+$HOME" ] || err $LINENO
+
+res=$($com <<< 'cd /tmp ; pushd -n /usr > /dev/null ; cd / ; dirs ; dirs -c ; dirs')
+[ "$res" == "/ /usr
+/" ] || err $LINENO
+
+res=$($com <<< 'popd')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'pushd')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'pushd /nonexist')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'dirs +3')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'dirs -x')
+[ "$?" == "2" ] || err $LINENO
+
 # builtin command
 #
 res=$($com <<< 'builtin cd; pwd')
@@ -139,11 +232,176 @@ res=$($com <<< 'echo あ い う | while read a b ; do echo $a ; echo $b ; done'
 [ "$res" == "あ
 い う" ] || err $LINENO
 
+res=$($com <<< 'IFS=: read a b <<< "1:2:3:" ; echo "[$a][$b]"')
+[ "$res" == "[1][2:3:]" ] || err $LINENO
+
+res=$($com <<< 'IFS=: read a b <<< "1:2:" ; echo "[$a][$b]"')
+[ "$res" == "[1][2]" ] || err $LINENO
+
+res=$($com <<< 'read a b <<< "  x   y  z  " ; echo "[$a][$b]"')
+[ "$res" == "[x][y  z]" ] || err $LINENO
+
+res=$($com <<< 'IFS=" :" read a b c <<< " 1 :: 2 " ; echo "[$a][$b][$c]"')
+[ "$res" == "[1][][2]" ] || err $LINENO
+
+res=$($com <<< 'read <<< "  x\ y  " ; echo "[$REPLY]"')
+[ "$res" == "[  x y  ]" ] || err $LINENO
+
+res=$($com <<< 'read -r <<< "  x\ y  " ; echo "[$REPLY]"')
+[ "$res" == "[  x\ y  ]" ] || err $LINENO
+
+res=$($com <<< 'printf "a\\\\\nb\n" | { read x ; echo $x ; }')
+[ "$res" == "ab" ] || err $LINENO
+
+res=$($com <<< 'read -a arr <<< " p q  r " ; echo ${#arr[@]} ${arr[1]}')
+[ "$res" == "3 q" ] || err $LINENO
+
+res=$($com <<< 'read -n 2 x <<< abcdef ; echo $x')
+[ "$res" == "ab" ] || err $LINENO
+
+res=$($com <<< 'read -N 3 x <<< $'"'"'a\nbcd'"'"' ; echo "[$x]"')
+[ "$res" == "[a
+b]" ] || err $LINENO
+
+res=$($com <<< 'read -d , x <<< "ab,cd" ; echo $x')
+[ "$res" == "ab" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 | read -t 0.2 x ; echo $?')
+[ "$res" == "142" ] || err $LINENO
+
+res=$($com <<< 'read -u 3 l 3< /etc/passwd ; echo ${l%%:*}')
+[ "$res" == "root" ] || err $LINENO
+
+res=$($com <<< '{ read l ; cat ; } <<< "one
+two"')
+[ "$res" == "two" ] || err $LINENO
+
+res=$($com <<< 'read x
+hello
+echo $x')
+[ "$res" == "hello" ] || err $LINENO
+
+res=$($com <<< 'printf ab | { read x ; echo $? $x ; }')
+[ "$res" == "1 ab" ] || err $LINENO
+
+res=$($com <<< 'read -u 5 x')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'read 1a <<< x')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'read -x')
+[ "$?" == "2" ] || err $LINENO
+
+# mapfile
+
+res=$($com <<< 'seq 5 | { mapfile -t a ; echo ${a[@]} ${#a[@]} ; }')
+[ "$res" == "1 2 3 4 5 5" ] || err $LINENO
+
+res=$($com <<< 'seq 2 | { mapfile ; echo "[${MAPFILE[0]}]" ; }')
+[ "$res" == "[1
+]" ] || err $LINENO
+
+res=$($com <<< 'seq 5 | { mapfile -t -n 2 -s 1 a ; echo ${a[@]} ; }')
+[ "$res" == "2 3" ] || err $LINENO
+
+res=$($com <<< 'a=(x y z) ; seq 2 | { mapfile -t -O 1 a ; echo ${a[@]} ; }')
+[ "$res" == "x 1 2" ] || err $LINENO
+
+res=$($com <<< 'printf "a,b,c" | { readarray -t -d , a ; echo ${a[2]} ${#a[@]} ; }')
+[ "$res" == "c 3" ] || err $LINENO
+
+res=$($com <<< 'cb () { echo "cb $1 $2" ; } ; seq 5 | mapfile -t -C cb -c 2 a')
+[ "$res" == "cb 1 2
+cb 3 4" ] || err $LINENO
+
+res=$($com <<< 'seq 5 | { mapfile -t -n 2 a ; cat ; }')
+[ "$res" == "3
+4
+5" ] || err $LINENO
+
+res=$($com <<< 'mapfile -n x a < /dev/null')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'mapfile -c 0 a < /dev/null')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'mapfile 1a < /dev/null')
+[ "$?" == "1" ] || err $LINENO
+
+# type, hash
+
+res=$($com <<< 'f () { echo a ; } ; type -t if f cd ls nope')
+[ "$res" == "keyword
+function
+builtin
+file" ] || err $LINENO
+
+res=$($com <<< 'type cd if')
+[ "$res" == "cd is a shell builtin
+if is a shell keyword" ] || err $LINENO
+
+res=$($com <<< 'type nope')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'type -P ls')
+[ "$res" == "$(type -P ls)" ] || err $LINENO
+
+res=$($com <<< 'type -p cd')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'ls > /dev/null ; type ls')
+[[ "$res" =~ ^"ls is hashed (" ]] || err $LINENO
+
+res=$($com <<< 'hash')
+[ "$res" == "hash: hash table empty" ] || err $LINENO
+
+res=$($com <<< 'ls > /dev/null ; ls > /dev/null ; hash')
+[ "$res" == "hits	command
+   2	$(type -P ls)" ] || err $LINENO
+
+res=$($com <<< 'hash ls ; hash -l')
+[ "$res" == "builtin hash -p $(type -P ls) ls" ] || err $LINENO
+
+res=$($com <<< 'hash -p /bin/echo foo ; foo hi ; hash -t foo')
+[ "$res" == "hi
+/bin/echo" ] || err $LINENO
+
+res=$($com <<< 'hash ls ; PATH=/nonexist:$PATH ; hash')
+[ "$res" == "hash: hash table empty" ] || err $LINENO
+
+res=$($com <<< 'hash ls ; PATH=$PATH ; hash')
+[ "$res" == "hash: hash table empty" ] || err $LINENO
+
+res=$($com <<< 'hash ls ; hash -r ; hash -d ls')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'hash -p /nonexist bar ; bar')
+[ "$?" == "127" ] || err $LINENO
+
+res=$($com <<< 'shopt -s checkhash ; hash -p /nonexist ls ; ls -d /')
+[ "$res" == "/" ] || err $LINENO
+
+res=$($com <<< 'f () { : ; } ; command -v f cd ls')
+[ "$res" == "f
+cd
+$(type -P ls)" ] || err $LINENO
+
 # set command
 
 res=$($com <<< 'set -- a b c ; echo $2')
 [ "$res" == "b" ] || err $LINENO
 
+res=$($com <<< 'set -e -- a b ; echo $- $2 ; set -x - c ; echo $- $1 ; set - ; echo $1')
+[ "$res" == "hBe b
+hBe c
+c" ] || err $LINENO
+
+res=$($com <<< 'set -Z ; echo $?' 2>&1)
+[[ "$res" =~ ": line 1: set: -Z: invalid option
+set: usage: set ".*"
+2"$ ]] || err $LINENO
+
 # shopt command
 
 res=$($com <<< 'shopt -u extglob ; echo @(a)')
@@ -154,6 +412,41 @@ echo @(a)')
 [ "$?" == "2" ] || err $LINENO
 [ "$res" == "" ] || err $LINENO
 
+res=$($com <<< 'shopt -q extglob && echo a; shopt -q extglob nullglob || echo b; shopt -qo errexit || echo c')
+[ "$res" == "a
+b
+c" ] || err $LINENO
+
+res=$($com <<< 'shopt -p extglob nullglob; shopt -po pipefail; shopt -os pipefail; shopt -o pipefail')
+[ "$res" == "shopt -s extglob
+shopt -u nullglob
+set +o pipefail
+pipefail        on" ] || err $LINENO
+
+res=$($com <<< 'shopt -u extglob; s="$(shopt -p)"; shopt -s extglob; eval "$s"; shopt extglob')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "extglob         off" ] || err $LINENO
+
+res=$($com <<< 'set -e -o pipefail; set +o > /tmp/rusty_bash_set_o; set +e +o pipefail; source /tmp/rusty_bash_set_o; echo $-; [[ -o pipefail ]]')
+[ "$?" == "0" ] || err $LINENO
+[ "$res" == "hBe" ] || err $LINENO
+
+res=$($com <<< 'shopt -s foo extglob; echo $?; shopt -o extglob; echo $?' 2>&1)
+echo "$res" | grep -q 'shopt: foo: invalid shell option name' || err $LINENO
+echo "$res" | grep -q 'shopt: extglob: invalid option name' || err $LINENO
+[ "$(echo "$res" | grep -v shopt)" == "1
+1" ] || err $LINENO
+
+res=$($com <<< 'shopt -s -u extglob' 2>&1)
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'shopt -so foo; echo $?; shopt -so posix; echo $?; shopt -so nounset; echo $?' 2>&1)
+echo "$res" | grep -q 'shopt: foo: invalid option name' || err $LINENO
+echo "$res" | grep -q 'shopt: posix: not supported yet' || err $LINENO
+[ "$(echo "$res" | grep -v shopt)" == "1
+1
+0" ] || err $LINENO
+
 # local
 
 res=$($com -c 'A=1 ; f () { local -a A ; A[1]=123 ; echo ${A[@]} ; } ; f ; echo $A')
@@ -246,6 +539,49 @@ echo flag:$flag OPTIND:$OPTIND exit:$res
 flag:s OPTIND:2 exit:0
 flag:? OPTIND:3 exit:1" ] || err $LINENO
 
+res=$($com <<< 'while getopts ab:c opt -a -b x -cbfile -ac rest ; do echo $opt $OPTARG $OPTIND ; done ; echo $OPTIND')
+[ "$res" = "a 2
+b x 4
+c 4
+b file 5
+a 5
+c 6
+6" ] || err $LINENO
+
+res=$($com <<< 'getopts ab opt -z 2>&1 ; echo $opt $OPTIND')
+[[ "$res" =~ ": illegal option -- z
+? 2"$ ]] || err $LINENO
+
+res=$($com <<< 'getopts ab: opt -b 2>&1 ; echo $opt $OPTIND')
+[[ "$res" =~ ": option requires an argument -- b
+? 2"$ ]] || err $LINENO
+
+res=$($com <<< 'OPTERR=0 ; getopts ab opt -z 2>&1 ; echo $opt')
+[ "$res" = "?" ] || err $LINENO
+
+res=$($com <<< 'getopts :ab: opt -z -b 2>&1 ; echo $opt $OPTARG ; getopts :ab: opt -z -b 2>&1 ; echo $opt $OPTARG')
+[ "$res" = "? z
+: b" ] || err $LINENO
+
+res=$($com <<< 'f () { OPTIND=1 ; while getopts a: opt "$@" ; do echo $opt $OPTARG ; done ; } ; f -a 1 ; f -a2')
+[ "$res" = "a 1
+a 2" ] || err $LINENO
+
+res=$($com <<< 'set -- -x ; getopts x opt -y ; echo $? $opt ; OPTIND=1 ; getopts x opt ; echo $? $opt')
+[ "$res" = "0 ?
+0 x" ] || err $LINENO
+
+res=$($com <<< 'getopts a 1opt -a')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'set -- -abc ; getopts abc o ; echo $o $OPTIND ; set -- -x ; getopts x o ; echo $o $?')
+[ "$res" = "a 1
+x 0" ] || err $LINENO
+
+res=$($com <<< 'set a -bc ; echo "$0|$@|$#" ; set -- -bc ; echo "$@|$#"')
+[ "$res" = "$com|a -bc|2
+-bc|1" ] || err $LINENO
+
 ### printf ###
 
 res=$($com <<< 'printf -v a %s bbb &> /dev/null; echo $a')
@@ -270,10 +606,62 @@ res=$($com <<< 'printf %s abc > /dev/null')
 res=$($com <<< 'printf %s abc &> /dev/null')
 [ "$res" = "" ] || err $LINENO
 
+res=$($com <<< 'printf "%s-%s\n" a b c')
+[ "$res" = "a-b
+c-" ] || err $LINENO
+
+res=$($com <<< 'printf -v a "%s," x y z; echo $a')
+[ "$res" = "x,y,z," ] || err $LINENO
+
+res=$($com <<< 'printf "[%*d][%-*d][%.*f]" 5 1 5 2 2 3.14159')
+[ "$res" = "[    1][2    ][3.14]" ] || err $LINENO
+
+res=$($com <<< 'printf "%*d" -9223372036854775808 1 | wc -c')
+[ "$res" = "2147483647" ] || err $LINENO
+
+res=$($com <<< 'a=(x y); i=1; printf -v "a[i+1]" z; declare -A b; printf -v "b[i+1]" w; echo ${a[@]} ${b[i+1]}')
+[ "$res" = "x y z w" ] || err $LINENO
+
+res=$($com <<< "printf '%c %i %u %o %x %X %d' abc 42 -1 8 255 255 \"'A\"")
+[ "$res" = "a 42 18446744073709551615 10 ff FF 65" ] || err $LINENO
+
+res=$($com <<< 'printf "%e %g %.1f" 3.5 0.0001 2.25')
+[ "$res" = "3.500000e+00 0.0001 2.2" ] || err $LINENO
+
+res=$($com <<< 'printf "%b" "a\tb\0101\101\c zzz"; printf "end"')
+[ "$res" = "a	bAAend" ] || err $LINENO
+
+res=$($com <<< "printf '%q ' 'a b' \"it's\" '~x' '' 'a,b' \$'a\\tb'")
+[ "$res" = "a\ b it\'s \~x '' a\,b \$'a\tb' " ] || err $LINENO
+
+res=$($com <<< 'TZ=UTC printf "%(%Y-%m-%d %H)T" 3600')
+[ "$res" = "1970-01-01 01" ] || err $LINENO
+
+res=$($com <<< 'printf "%(%Y)T" -1')
+[ "$res" = "$(date +%Y)" ] || err $LINENO
+
+res=$(TZ=JST-9 $com <<< 'TZ= ; printf "%(%H)T" 0')
+[ "$res" = "00" ] || err $LINENO
+
+res=$(env -u TZ $com <<< 'TZ=JST-9 ; printf "%(%H)T\n" 0 ; env | grep -c ^TZ=')
+[ "$res" = "09
+0" ] || err $LINENO
+
+res=$($com <<< 'printf "%d\n" abc 12abc; echo $?')
+[ "$res" = "0
+12
+1" ] || err $LINENO
+
+res=$($com <<< 'printf "%d" abc 2>&1')
+[[ "$res" =~ "line 1: printf: abc: invalid number" ]] || err $LINENO
+
+res=$($com <<< 'printf "%z"; echo $?')
+[ "$res" = "1" ] || err $LINENO
+
 ### trap ###
 #
-res=$($com <<< 'trap "echo hoge" 4') # 4 (SIGILL) is forbidden by signal_hook
-[ $? -eq 1 ] || err $LINENO
+res=$($com <<< 'trap "echo hoge" 4 ; trap -p 4')
+[ "$res" = "trap -- 'echo hoge' SIGILL" ] || err $LINENO
 
 res=$($com <<< 'trap "echo hoge" QUIT; kill -3 $$; sleep 1')
 [ "$res" = "hoge" ] || err $LINENO
@@ -285,5 +673,259 @@ res=$($com <<< 'trap "echo hoge" EXIT; echo fuge')
 [ "$res" = "fuge
 hoge" ] || err $LINENO
 
+res=$($com <<< "trap \"echo it's\" INT ; trap '' QUIT ; trap 'echo bye' EXIT ; trap")
+[ "$res" = "trap -- 'echo bye' EXIT
+trap -- 'echo it'\\''s' SIGINT
+trap -- '' SIGQUIT
+bye" ] || err $LINENO
+
+res=$($com <<< 'trap "echo a" INT TERM ; trap - INT ; trap QUIT ; trap 15 ; trap')
+[ "$res" = "" ] || err $LINENO
+
+res=$($com <<< 'trap "echo a" USR1 ; trap "echo b" USR1 ; kill -USR1 $$ ; echo c')
+[ "$res" = "b
+c" ] || err $LINENO
+
+res=$($com <<< 'trap "" USR1 ; kill -USR1 $$ ; echo a ; (kill -USR1 $BASHPID ; echo b)')
+[ "$res" = "a
+b" ] || err $LINENO
+
+res=$($com <<< 'trap "echo a" USR1 ; (kill -USR1 $BASHPID ; echo b) ; echo $?')
+[ "$res" = "138" ] || err $LINENO
+
+res=$($com <<< 'trap -l | head -1')
+[ "$res" = " 1) SIGHUP	 2) SIGINT	 3) SIGQUIT	 4) SIGILL	 5) SIGTRAP" ] || err $LINENO
+
+res=$($com <<< 'trap "echo a" NOSIG')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'trap -x')
+[ $? -eq 2 ] || err $LINENO
+
+res=$($com <<< 'trap "echo E \$LINENO" ERR
+false
+false || true
+! true
+if false; then :; fi
+true && false
+f () { false; }
+f
+set -E
+f
+x=$(false)')
+[ "$res" = "E 2
+E 6
+E 8
+E 7
+E 10
+E 11" ] || err $LINENO
+
+res=$($com <<< "trap 'echo \"> \$BASH_COMMAND\"' DEBUG ; echo a ; f () { echo b; } ; f ; trap - DEBUG ; trap -p")
+[ "$res" = "> echo a
+a
+> f
+b
+> trap - DEBUG" ] || err $LINENO
+
+res=$($com <<< 'trap "echo R" RETURN ; f () { echo f; } ; f ; . /dev/null ; set -T ; f')
+[ "$res" = "f
+R
+f
+R" ] || err $LINENO
+
+res=$($com <<< 'f () { trap "echo R \${FUNCNAME[0]}" RETURN ; } ; f ; g () { :; } ; g')
+[ "$res" = "R f" ] || err $LINENO
+
+res=$($com <<< 'trap "echo usr1" USR1 ; (sleep 0.3 ; kill -USR1 $$) & sleep 3 & wait $! ; echo st=$?' 2> /dev/null | grep -e usr1 -e st=)
+[ "$res" = "usr1
+st=138" ] || err $LINENO
+
+res=$($com <<< 'trap "echo x" INT ; ( trap -p INT ; ( trap ) ; trap "echo y" USR1 ; trap ) ; saved=$(trap) ; echo "$saved"')
+[ "$res" = "trap -- 'echo x' SIGINT
+trap -- 'echo x' SIGINT
+trap -- 'echo y' SIGUSR1
+trap -- 'echo x' SIGINT" ] || err $LINENO
+
+# ulimit, umask
+
+res=$($com <<< 'ulimit -n 100 ; ulimit -n ; ulimit -S -n')
+[ "$res" = "100
+100" ] || err $LINENO
+
+res=$($com <<< 'ulimit -S -c 0 ; ulimit -c')
+[ "$res" = "0" ] || err $LINENO
+
+res=$($com <<< 'ulimit -S -s 4096 ; ulimit -s ; ulimit -H -s hard ; ulimit -H -s')
+[ "$res" = "4096
+$(ulimit -H -s)" ] || err $LINENO
+
+res=$($com <<< 'ulimit -a | grep -c "open files  *(-n)"')
+[ "$res" = "1" ] || err $LINENO
+
+res=$($com <<< 'ulimit -S -n 50 ; ulimit -n -p')
+[ "$res" = "open files                          (-n) 50
+pipe size                (512 bytes, -p) 8" ] || err $LINENO
+
+res=$($com <<< 'ulimit -n abc')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'ulimit -p 10')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'ulimit -Z')
+[ $? -eq 2 ] || err $LINENO
+
+res=$($com <<< 'umask 022 ; umask ; umask -S ; umask -p ; umask -p -S')
+[ "$res" = "0022
+u=rwx,g=rx,o=rx
+umask 0022
+umask -S u=rwx,g=rx,o=rx" ] || err $LINENO
+
+res=$($com <<< 'umask u=rwx,g=rx,o= ; umask ; umask g+w,o+x ; umask ; umask a-w ; umask -S')
+[ "$res" = "0027
+0006
+u=rx,g=rx,o=x" ] || err $LINENO
+
+res=$($com <<< 'umask 7777 ; umask ; umask = ; umask -S')
+[ "$res" = "0777
+u=,g=,o=" ] || err $LINENO
+
+res=$($com <<< 'umask 027 ; rm -f /tmp/rusty_bash_umask ; touch /tmp/rusty_bash_umask ; ls -l /tmp/rusty_bash_umask')
+[[ "$res" =~ ^-rw-r-----" " ]] || err $LINENO
+
+res=$($com <<< 'umask 089')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'umask u=q')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'umask -x')
+[ $? -eq 2 ] || err $LINENO
+
+# let, times, caller
+
+res=$($com <<< 'let a=1+2 b=a*2 ; echo $a $b $?')
+[ "$res" = "3 6 0" ] || err $LINENO
+
+res=$($com <<< 'let "c = 5" c++ ; echo $c')
+[ "$res" = "6" ] || err $LINENO
+
+res=$($com <<< 'let 0')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'let')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'let 1+')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< "let 'z=1+'" 2>&1)
+[[ "$res" =~ "(error token is \"+\")"$ ]] || err $LINENO
+
+res=$($com <<< "let '1?2:' ; echo \$?" 2>&1)
+[[ "$res" =~ "(error token is \":\")
+1"$ ]] || err $LINENO
+
+res=$($com <<< 'times | wc -l')
+[ "$res" = "2" ] || err $LINENO
+
+res=$($com <<< 'times')
+[[ "$res" =~ ^[0-9]+m[0-9]+\.[0-9]{3}s\ [0-9]+m[0-9]+\.[0-9]{3}s ]] || err $LINENO
+
+cat << 'EOF' > /tmp/rusty_bash_caller_src
+h () { caller 0 ; caller 1 ; caller 2 ; }
+h
+EOF
+cat << 'EOF' > /tmp/rusty_bash_caller
+f () { caller ; caller 0 ; caller 1 ; echo $? ; g ; }
+g () { caller 0 ; caller 1 ; }
+f
+caller
+source /tmp/rusty_bash_caller_src
+EOF
+res=$($com /tmp/rusty_bash_caller)
+[ "$res" = "3 /tmp/rusty_bash_caller
+3 main /tmp/rusty_bash_caller
+1
+1 f /tmp/rusty_bash_caller
+3 main /tmp/rusty_bash_caller
+0 NULL
+2 source /tmp/rusty_bash_caller_src
+5 main /tmp/rusty_bash_caller" ] || err $LINENO
+
+res=$($com <<< 'f () { caller ; } ; f')
+[ "$res" = "1 NULL" ] || err $LINENO
+
+res=$($com <<< 'caller')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'f () { caller x ; } ; f')
+[ $? -eq 2 ] || err $LINENO
+
+### HELP ###
+
+res=$($com <<< 'help -s cd pwd')
+[ "$res" = "cd: cd [-L|[-P [-e]] [-@]] [dir]
+pwd: pwd [-LP]" ] || err $LINENO
+
+res=$($com <<< 'help -d "pu*" popd')
+[ "$res" = "Shell commands matching keywords \`pu*, popd'
+
+popd - Remove directories from stack.
+pushd - Add directories to stack." ] || err $LINENO
+
+res=$($com <<< 'help -s tr')
+[ "$res" = "trap: trap [-lp] [[arg] signal_spec ...]
+true: true" ] || err $LINENO
+
+res=$($com <<< 'help true | head -2')
+[ "$res" = "true: true
+    Return a successful result." ] || err $LINENO
+
+res=$($com <<< 'help -m : | head -2')
+[ "$res" = "NAME
+    : - Null command." ] || err $LINENO
+
+res=$($com <<< 'help nosuchtopic')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'help -x')
+[ $? -eq 2 ] || err $LINENO
+
+res=$($com <<< 'umask -x 2>&1 | tail -1')
+[ "$res" = "umask: usage: umask [-p] [-S] [mode]" ] || err $LINENO
+
+res=$($com <<< 'unalias 2>&1 ; echo $?')
+[ "$res" = "unalias: usage: unalias [-a] name [name ...]
+2" ] || err $LINENO
+
+res=$($com <<< 'for b in $(compgen -b) ; do help -s $b > /dev/null || echo $b ; done')
+[ "$res" = "" ] || err $LINENO
+
+res=$($com <<< 'compgen -A helptopic pu')
+[ "$res" = "pushd" ] || err $LINENO
+
+### ENABLE ###
+
+res=$($com <<< 'enable -n pwd ; type -t pwd ; enable -n ; enable pwd ; type -t pwd')
+[ "$res" = "file
+enable -n pwd
+builtin" ] || err $LINENO
+
+res=$($com <<< 'enable -n true ; enable -a | grep -e " true" -e " false"')
+[ "$res" = "enable false
+enable -n true" ] || err $LINENO
+
+res=$($com <<< 'enable -ps | head -3')
+[ "$res" = "enable .
+enable :
+enable break" ] || err $LINENO
+
+res=$($com <<< 'enable nosuchbuiltin')
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< 'enable -x')
+[ $? -eq 2 ] || err $LINENO
+
 echo $0 >> ./ok
 
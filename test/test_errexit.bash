@@ -0,0 +1,146 @@
+#!/bin/bash -xv
+# SPDX-FileCopyrightText: 2026 Ryuichi Ueda ryuichiueda@gmail.com
+# SPDX-License-Identifier: BSD-3-Clause
+
+err () {
+	echo $0 >> ./error
+	echo "ERROR!" FILE: $0, LINENO: $1
+	exit 1
+}
+
+[ "$1" == "nobuild" ] || cargo build --release || err $LINENO
+
+cd $(dirname $0)
+com=../target/release/sush
+
+### conditions
+
+res=$($com <<< 'set -e; if false; then echo NG; fi; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; if true; then false; fi; echo NG')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; while false; do :; done; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; while true; do false; done; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; if false; then :; elif false; then :; else echo OK; fi')
+[ "$res" == "OK" ] || err $LINENO
+
+### AND-OR lists
+
+res=$($com <<< 'set -e; false && true; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; true && false; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; false || false; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; false | true && false; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; { false; } || echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; [[ a == b ]] && true; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+### ! pipelines
+
+res=$($com <<< 'set -e; ! true; ! false; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; ! true
+false; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { false; echo in; }; ! f; echo OK')
+[ "$res" == "in
+OK" ] || err $LINENO
+
+### compound commands
+
+res=$($com <<< 'set -e; { false && true; }; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; for i in 1; do false && true; done; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; case a in a) false && true ;; esac; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; (( 0 )); echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; (false && true); echo NG')
+[ "$res" == "" ] || err $LINENO
+
+### functions
+
+res=$($com <<< 'set -e; f () { false && true; }; f; echo NG')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { for i in 1; do false && true; done; }; f; echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { false; echo in; }; if f; then echo OK; fi')
+[ "$res" == "in
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { false; echo in; }; f && echo OK')
+[ "$res" == "in
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { while false; do :; done; false; echo in; }; if f; then echo OK; fi')
+[ "$res" == "in
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { ( false; echo in ); }; f || echo NG')
+[ "$res" == "in" ] || err $LINENO
+
+### subshells and command substitutions
+
+res=$($com <<< 'set -e; (false; echo NG); echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; x=$(false); echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -e; echo $(false; echo in); echo OK')
+[ "$res" == "in
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; shopt -s inherit_errexit; echo $(false; echo NG); echo OK')
+[ "$res" == "
+OK" ] || err $LINENO
+
+res=$($com <<< 'set -e; f () { local x=$(false); echo OK; }; f')
+[ "$res" == "OK" ] || err $LINENO
+
+### ERR trap
+
+res=$($com <<< 'trap "echo ERR" ERR; { false && true; }; for i in 1; do false && true; done; echo OK')
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'trap "echo ERR" ERR; f () { false && true; }; f; echo OK')
+[ "$res" == "ERR
+OK" ] || err $LINENO
+
+res=$($com <<< 'trap "echo ERR" ERR; { false; }; echo OK')
+[ "$res" == "ERR
+OK" ] || err $LINENO
+
+### EXIT trap
+
+res=$($com <<< 'set -e; trap "echo bye" EXIT; false; echo NG')
+[ "$?" == "1" ] || err $LINENO
+[ "$res" == "bye" ] || err $LINENO
+
+echo $0 >> ./ok
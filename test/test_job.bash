@@ -20,7 +20,115 @@ echo "$res" | grep -F '[1] ' || err $LINENO
 echo "$res" | grep -F '[2]- ' || err $LINENO
 echo "$res" | grep -F '[3]+ ' || err $LINENO
 
-res=$($com <<< 'sleep 5 | rev | cat & sleep 1 ; killall -SIGSTOP cat ; jobs')
+res=$($com <<< 'set -m; sleep 5 | rev | cat & sleep 1 ; killall -SIGSTOP cat ; jobs')
 echo "$res" | grep Stopped || err $LINENO
 
+res=$($com <<< 'sleep 5 & kill %1 ; sleep 0.2 ; jobs')
+echo "$res" | grep Terminated || err $LINENO
+
+res=$($com <<< 'sleep 5 & kill -STOP %1 ; sleep 0.2 ; kill %1 ; sleep 0.2 ; jobs')
+echo "$res" | grep Terminated || err $LINENO
+
+res=$($com <<< 'sleep 5 & kill -s KILL %1 ; sleep 0.2 ; jobs')
+echo "$res" | grep Killed || err $LINENO
+
+res=$($com <<< 'kill -l 9 15 137 INT')
+[ "$res" == "KILL
+TERM
+KILL
+2" ] || err $LINENO
+
+res=$($com <<< 'kill -l | head -1')
+[ "$res" == " 1) SIGHUP	 2) SIGINT	 3) SIGQUIT	 4) SIGILL	 5) SIGTRAP" ] || err $LINENO
+
+res=$($com <<< 'kill %3')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'kill -XYZ 1')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'kill')
+[ "$?" == "2" ] || err $LINENO
+
+res=$($com <<< 'sleep 0.1 & [ "$!" -gt 0 ] && echo ok' | head -n 1)
+[ "$res" == "ok" ] || err $LINENO
+
+res=$($com <<< '(exit 3) & wait $! ; echo $?' | head -n 1)
+[ "$res" == "3" ] || err $LINENO
+
+res=$($com <<< 'wait 99999')
+[ "$?" == "127" ] || err $LINENO
+
+res=$($com <<< 'wait %7')
+[ "$?" == "127" ] || err $LINENO
+
+res=$($com <<< 'sleep 0.5 & (sleep 0.1 ; exit 5) & wait -n ; echo $?' | head -n 1)
+[ "$res" == "5" ] || err $LINENO
+
+res=$($com <<< '(exit 4) & p=$! ; wait -n -p v ; echo $? ; [ "$v" == "$p" ] && echo ok' | head -n 2)
+[ "$res" == "4
+ok" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 1 & jobs -p > /tmp/rusty_bash_jobs ; wc -l < /tmp/rusty_bash_jobs')
+[ "$res" == "2" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 1 & kill -STOP %1 ; sleep 0.1 ; jobs -s > /tmp/rusty_bash_jobs ; jobs -r >> /tmp/rusty_bash_jobs ; kill -CONT %1 ; cat /tmp/rusty_bash_jobs')
+echo "$res" | head -1 | grep -F '[1]' | grep -q Stopped || err $LINENO
+echo "$res" | sed -n 2p | grep -F '[2]' | grep -q Running || err $LINENO
+
+res=$($com <<< 'sleep 1 & jobs -l > /tmp/rusty_bash_jobs ; grep -F "[1]+ $! " /tmp/rusty_bash_jobs')
+[ "$?" == "0" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & jobs -x echo %1 > /tmp/rusty_bash_jobs ; [ "$(cat /tmp/rusty_bash_jobs)" == "$!" ] && echo ok' | head -n 1)
+[ "$res" == "ok" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 1 & disown %1 ; jobs > /tmp/rusty_bash_jobs ; wc -l < /tmp/rusty_bash_jobs')
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 1 & disown -a ; jobs > /tmp/rusty_bash_jobs ; wc -l < /tmp/rusty_bash_jobs')
+[ "$res" == "0" ] || err $LINENO
+
+res=$($com <<< 'disown')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'suspend')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 2 & jobs %% ; jobs %- ; jobs %?2 ; jobs %1' 2> /dev/null | grep -o '^\[[0-9]\]')
+[ "$res" == "[2]
+[1]
+[2]
+[1]" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & sleep 2 & jobs %sl' 2>&1 | grep -o 'sl: ambiguous job spec')
+[ "$res" == "sl: ambiguous job spec" ] || err $LINENO
+
+res=$($com <<< 'sleep 1 & jobs %2' 2>&1 | grep -o '%2: no such job')
+[ "$res" == "%2: no such job" ] || err $LINENO
+
+res=$($com <<< 'set -b ; sleep 0.1 & sleep 0.5 ; echo end' 2> /dev/null)
+[ "$(echo "$res" | grep -o -e Done -e end)" == "Done
+end" ] || err $LINENO
+
+res=$($com <<< 'sleep 0.1 & sleep 0.5 ; echo end' 2> /dev/null)
+[ "$(echo "$res" | grep -o -e Done -e end)" == "end
+Done" ] || err $LINENO
+
+res=$($com <<< 'sleep 0.1 & wait $!; jobs; wait %1' 2>&1)
+echo "$res" | grep -q Done && err $LINENO
+echo "$res" | grep -q '%1: no such job' || err $LINENO
+
+res=$($com <<< 'sleep 0.1 & wait; jobs' 2>&1)
+echo "$res" | grep -q Done && err $LINENO
+
+$com -c 'set -m; suspend -f; echo resumed' > /tmp/rusty_bash_suspend 2>&1 &
+pid=$!
+sleep 1
+ps -o stat= -p $pid | grep -q T || err $LINENO
+kill -CONT $pid
+wait $pid
+[ "$(cat /tmp/rusty_bash_suspend)" == "resumed" ] || err $LINENO
+
+rm -f /tmp/rusty_bash_jobs /tmp/rusty_bash_suspend
+
 echo $0 >> ./ok
@@ -71,5 +71,189 @@ res=$($com <<< 'set +B; echo {a,b}')
 res=$($com <<< 'set -o noglob; echo /etc/*')
 [ "$res" = "/etc/*" ] || err $LINENO
 
+### -u
+
+res=$($com <<< 'set -u; echo $X; echo NG' 2>&1)
+[ "$?" == "127" ] || err $LINENO
+[ "$(echo "$res" | grep -o 'X: unbound variable')" == "X: unbound variable" ] || err $LINENO
+
+res=$($com <<< 'set -u; f () { echo $2 ; } ; f a ; echo NG' 2>&1)
+echo "$res" | grep -q '\$2: unbound variable' || err $LINENO
+echo "$res" | grep -q NG && err $LINENO
+
+res=$($com <<< 'set -o nounset; echo "$@" $* ${X-a} ${X:-b} ${X+c}${X:+d} ${#A[@]}')
+[ "$res" == " a b 0" ] || err $LINENO
+
+res=$($com <<< 'set -u; A=(1); echo ${A[0]} ; echo ${A[2]} ; echo NG' 2>&1)
+echo "$res" | grep -q 'A\[2\]: unbound variable' || err $LINENO
+echo "$res" | grep -q NG && err $LINENO
+
+res=$($com <<< 'set -euo pipefail; [[ -z "${A[*]}${A[@]}" ]]; echo "${A[@]}OK"' 2>&1)
+[ "$res" == "OK" ] || err $LINENO
+
+res=$($com <<< 'set -u; echo $! ; echo NG' 2>&1)
+echo "$res" | grep -q '\$!: unbound variable' || err $LINENO
+echo "$res" | grep -q NG && err $LINENO
+
+res=$($com <<< 'set -u; echo $(( Y + 1 )) ; echo NG' 2>&1)
+echo "$res" | grep -q 'Y: unbound variable' || err $LINENO
+echo "$res" | grep -q NG && err $LINENO
+
+res=$($com <<< 'set -u; ( echo $X ) ; echo $?' 2> /dev/null)
+[ "$res" == "1" ] || err $LINENO
+
+res=$($com <<< 'set -euo pipefail; echo $X; echo NG' 2> /dev/null)
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'X=1; Y= ; echo ${X:-a} ${X-b} ${Y:-c} ${Y-d}.')
+[ "$res" == "1 1 c ." ] || err $LINENO
+
+### -x
+
+res=$($com <<< 'set -x; echo "a b" "c'"'"'d" ""' 2>&1)
+[ "$res" == "+ echo 'a b' 'c'\''d' ''
+a b c'd " ] || err $LINENO
+
+res=$($com <<< 'PS4='"'"'+$LINENO: '"'"'
+set -x
+echo a' 2>&1)
+[ "$res" == "+3: echo a
+a" ] || err $LINENO
+
+res=$($com <<< 'set -x; x=$(echo a); eval "echo b"' 2>&1)
+[ "$res" == "++ echo a
++ x=a
++ eval 'echo b'
+++ echo b
+b" ] || err $LINENO
+
+res=$($com <<< 'set -x; for i in 1; do (( i + 1 )); [[ $i == 1 ]]; done' 2>&1)
+[ "$res" == "+ for i in 1
++ ((  i + 1  ))
++ [[ 1 == 1 ]]" ] || err $LINENO
+
+res=$($com <<< 'set -x; case a in a) : ;; esac' 2>&1)
+[ "$res" == "+ case a in
++ :" ] || err $LINENO
+
+res=$($com <<< '{ BASH_XTRACEFD=3; set -x; echo a; set +x; } 3> /tmp/rusty_bash_xtrace; cat /tmp/rusty_bash_xtrace' 2>&1)
+[ "$res" == "a
++ echo a
++ set +x" ] || err $LINENO
+
+### -v
+
+res=$($com <<< 'set -v
+echo a
+if true; then
+  echo b
+fi' 2>&1)
+[ "$res" == "echo a
+a
+if true; then
+  echo b
+fi
+b" ] || err $LINENO
+
+res=$($com <<< 'set -v; eval "echo a
+echo b"' 2>&1)
+[ "$res" == "echo a
+a
+echo b
+b" ] || err $LINENO
+
+res=$($com <<< 'set -vx; eval "echo a"' 2>&1)
+[ "$res" == "+ eval 'echo a'
+echo a
+++ echo a
+a" ] || err $LINENO
+
+echo 'echo a' > /tmp/rusty_bash_verbose
+res=$($com -v /tmp/rusty_bash_verbose 2>&1)
+[ "$res" == "echo a
+a" ] || err $LINENO
+
+res=$($com --verbose /tmp/rusty_bash_verbose 2>&1)
+[ "$res" == "echo a
+a" ] || err $LINENO
+
+res=$($com <<< 'set -v; source /tmp/rusty_bash_verbose' 2>&1)
+[ "$res" == "echo a
+a" ] || err $LINENO
+
+res=$($com -vc 'echo a
+echo b' 2>&1)
+[ "$res" == "echo a
+a
+echo b
+b" ] || err $LINENO
+
+### flags and SHELLOPTS
+
+res=$($com -c 'echo $-')
+[ "$res" == "hBc" ] || err $LINENO
+
+res=$($com <<< 'set -f; echo /etc/*; set +f; [ "$(echo /etc/*)" != "/etc/*" ] && echo ok')
+[ "$res" == "/etc/*
+ok" ] || err $LINENO
+
+res=$($com <<< 'set -a; X=abc; env | grep ^X=')
+[ "$res" == "X=abc" ] || err $LINENO
+
+res=$($com <<< 'set -n
+echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -t; echo a
+echo NG')
+[ "$res" == "a" ] || err $LINENO
+
+res=$($com <<< 'set -t
+echo NG')
+[ "$res" == "" ] || err $LINENO
+
+res=$($com <<< 'set -k; env X=abc | grep ^X=')
+[ "$res" == "X=abc" ] || err $LINENO
+
+res=$($com <<< 'set +h; hash')
+[ "$?" == "1" ] || err $LINENO
+
+res=$($com <<< 'set -o errtrace -o nounset; echo $SHELLOPTS')
+[ "$res" == "braceexpand:errtrace:hashall:interactive-comments:nounset" ] || err $LINENO
+
+res=$($com <<< 'set -o posix; echo $?; set -H; echo $?; set +o history; echo $?; [[ -o posix ]] || echo OK' 2>&1)
+[[ "$res" =~ "set: posix: not supported yet
+2
+".*"set: -H: not supported yet
+2
+0
+OK"$ ]] || err $LINENO
+
+res=$($com <<< 'cd /tmp; echo a > sush_noclobber; set -C; echo b > sush_noclobber; echo $?; cat sush_noclobber; echo c >| sush_noclobber; cat sush_noclobber; rm sush_noclobber' 2>&1)
+[[ "$res" =~ "sush_noclobber: cannot overwrite existing file
+1
+a
+c"$ ]] || err $LINENO
+
+res=$($com <<< 'set -o noclobber; echo a > /dev/null; echo $?')
+[ "$res" == "0" ] || err $LINENO
+
+res=$($com <<< 'shopt -s nullglob; echo $?' 2>&1)
+[[ "$res" =~ "nullglob: not supported yet
+1"$ ]] || err $LINENO
+
+res=$($com <<< 'SHELLOPTS=a; echo $?; unset SHELLOPTS; echo $?' 2> /dev/null)
+[ "$res" == "1
+1" ] || err $LINENO
+
+res=$($com <<< 'SHELLOPTS=a; BASHOPTS=b' 2>&1)
+[[ "$res" =~ ": line 1: SHELLOPTS: readonly variable
+".*": line 1: BASHOPTS: readonly variable"$ ]] || err $LINENO
+
+res=$(env SHELLOPTS=nounset $com <<< 'echo $-')
+[ "$res" == "hBu" ] || err $LINENO
+
+res=$($com <<< 'sleep 0 & fg' 2>&1)
+echo "$res" | grep -q 'fg: no job control' || err $LINENO
 
 echo $0 >> ./ok
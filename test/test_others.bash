@@ -706,4 +706,7 @@ FIN
 )
 [ "$res" == $'\U110000' ] || err $LINENO
 
+res=$($com < / 2>&1)
+[ "$res" == "sush: -: Is a directory" ] || err $LINENO
+
 echo $0 >> ./ok
@@ -60,6 +60,47 @@ res=$($com <<< 'declare -a arr ; arr=bbb ; echo ${arr[0]}')
 res=$($com <<< 'f(){ g () { echo ${FUNCNAME[@]} ;} ; g ;} ; f')
 [ "$res" == "g f" ] || err $LINENO
 
+### BASH_SOURCE, BASH_LINENO, BASH_ARGV, FUNCNEST ###
+
+cat << 'EOF' > /tmp/rusty_bash_stack_src
+echo "${BASH_SOURCE[*]} ${BASH_LINENO[*]}"
+EOF
+cat << 'EOF' > /tmp/rusty_bash_stack
+f () { echo "${FUNCNAME[*]} ${BASH_SOURCE[*]} ${BASH_LINENO[*]}" ; }
+echo "${BASH_SOURCE[*]} ${BASH_LINENO[*]}"
+f
+g () { f ; }
+g
+eval 'f'
+source /tmp/rusty_bash_stack_src
+EOF
+res=$($com /tmp/rusty_bash_stack)
+[ "$res" == "/tmp/rusty_bash_stack 0
+f main /tmp/rusty_bash_stack /tmp/rusty_bash_stack 3 0
+f g main /tmp/rusty_bash_stack /tmp/rusty_bash_stack /tmp/rusty_bash_stack 4 5 0
+f main /tmp/rusty_bash_stack /tmp/rusty_bash_stack 6 0
+/tmp/rusty_bash_stack_src /tmp/rusty_bash_stack 7 0" ] || err $LINENO
+
+res=$($com -c 'f () { echo "${BASH_ARGV[*]}|${BASH_ARGC[*]}" ; } ; f a b ; shopt -s extdebug ; f a b ; f ; echo "${BASH_ARGV[*]}|${BASH_ARGC[*]}"' x y)
+[ "$res" == "|
+b a y|2 1
+y|0 1
+y|1" ] || err $LINENO
+
+res=$($com -c 'g () { shopt -s extdebug ; } ; f () { g ; echo "${BASH_ARGV[*]}|${BASH_ARGC[*]}" ; } ; f a b ; echo "${BASH_ARGV[*]}|${BASH_ARGC[*]}" ; h () { shopt -u extdebug ; } ; f c ; h ; shopt -s extdebug ; f d ; echo "${BASH_ARGV[*]}|${BASH_ARGC[*]}"' x y)
+[ "$res" == "b a y|2 1
+y|1
+c y|1 1
+d y|1 1
+y|1" ] || err $LINENO
+
+res=$($com <<< 'FUNCNEST=2 ; a () { b ; echo a $? ; } ; b () { c ; echo b $? ; } ; c () { echo c ; } ; a')
+[ "$res" == "b 1
+a 0" ] || err $LINENO
+
+res=$($com <<< 'FUNCNEST=3 ; r () { r ; } ; r ; echo $?')
+[ "$res" == "1" ] || err $LINENO
+
 ### INDIRECT EXPANSION ###
 
 res=$($com -c 'A=B; B=100; echo ${!A}')
@@ -0,0 +1,51 @@
+#!/bin/bash -xv
+# SPDX-FileCopyrightText: 2024 Ryuichi Ueda ryuichiueda@gmail.com
+# SPDX-License-Identifier: BSD-3-Clause
+
+err () {
+	echo $0 >> ./error
+	echo "ERROR!" FILE: $0, LINENO: $1
+	exit 1
+}
+
+[ "$1" == "nobuild" ] || cargo build --release --workspace || err $LINENO
+
+cd $(dirname $0)
+com=../target/release/sush
+lib=libsush_plugin_example.so
+[ -e ../target/release/$lib ] || lib=libsush_plugin_example.dylib
+[ -e ../target/release/$lib ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib hello ; hello ; hello sush")
+[ "$res" = "Hello, world!
+Hello, sush!" ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib upper ; a=abc ; upper a ; echo \$a")
+[ "$res" = "ABC" ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib upper ; f () { local a=abc ; upper a ; echo \$a ; } ; f ; echo \$a")
+[ "$res" = "ABC" ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib hello ; hello | tr a-z A-Z")
+[ "$res" = "HELLO, WORLD!" ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib hello ; type -t hello ; enable -a | grep hello")
+[ "$res" = "builtin
+enable hello" ] || err $LINENO
+
+res=$($com <<< "BASH_LOADABLES_PATH=/nonexist:../target/release ; enable -f $lib hello ; hello")
+[ "$res" = "Hello, world!" ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib hello ; enable -d hello ; hello")
+[ $? -eq 127 ] || err $LINENO
+
+res=$($com <<< "enable -d hello")
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< "enable -f ../target/release/$lib nosuch")
+[ $? -eq 1 ] || err $LINENO
+
+res=$($com <<< "enable -f /nonexist/$lib hello")
+[ $? -eq 1 ] || err $LINENO
+
+echo $0 >> ./ok